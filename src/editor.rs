@@ -0,0 +1,196 @@
+use crate::game_of_life::World;
+use crate::patterns;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Cursor {
+    row: u8,
+    col: u8
+}
+
+impl Cursor {
+
+    pub fn position(&self) -> (u8, u8) {
+        (self.row, self.col)
+    }
+
+    /// Moves the cursor by the given offset, keeping it inside the world.
+    pub fn move_by(&mut self, d_row: i8, d_col: i8, world: &World) {
+
+        self.row = (self.row as i16 + d_row as i16).clamp(0, world.height() as i16 - 1) as u8;
+        self.col = (self.col as i16 + d_col as i16).clamp(0, world.width() as i16 - 1) as u8;
+    }
+
+    /// Overlays `pattern` into the world with its top-left corner under the cursor,
+    /// so a stable world resumes evolving.
+    pub fn stamp(&self, world: &mut World, pattern: &World) {
+        world.overlay(pattern, self.row, self.col);
+    }
+}
+
+/// Commands understood by `Editor`, independent of the terminal they are read from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Toggle,
+    Stamp,
+    Step,
+    Play,
+    Quit
+}
+
+/// Interactive editing session: a cursor moving over a world whose cells can be toggled or stamped with a glider,
+/// stepped one generation at a time or left playing.
+pub struct Editor {
+    world: World,
+    cursor: Cursor,
+    playing: bool
+}
+
+impl Editor {
+
+    pub fn new(world: World) -> Editor {
+        Editor { world, cursor: Cursor::default(), playing: false }
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn into_world(self) -> World {
+        self.world
+    }
+
+    pub fn cursor(&self) -> Cursor {
+        self.cursor
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Applies a single key, returning `false` once the session should end.
+    pub fn handle(&mut self, key: Key) -> bool {
+
+        match key {
+            Key::Up => self.cursor.move_by(-1, 0, &self.world),
+            Key::Down => self.cursor.move_by(1, 0, &self.world),
+            Key::Left => self.cursor.move_by(0, -1, &self.world),
+            Key::Right => self.cursor.move_by(0, 1, &self.world),
+            Key::Toggle => {
+                let (row, col) = self.cursor.position();
+                self.world.toggle_cell(col.into(), row.into());
+            },
+            Key::Stamp => {
+                let glider = patterns::glider();
+                self.cursor.stamp(&mut self.world, &World::new_rect(glider.cells, glider.width, glider.height).unwrap());
+            },
+            Key::Step => self.world.advance(),
+            Key::Play => self.playing = !self.playing,
+            Key::Quit => return false
+        }
+
+        true
+    }
+
+    /// Applies every key in order, stopping at the first one ending the session.
+    pub fn handle_all<I: IntoIterator<Item = Key>>(&mut self, keys: I) -> bool {
+        keys.into_iter().all(|key| self.handle(key))
+    }
+
+    /// Advances the world while playing, pausing once it is stable.
+    pub fn tick(&mut self) {
+
+        if !self.playing { return; }
+
+        self.world.advance();
+
+        if self.world.is_stable() { self.playing = false; }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::game_of_life::World;
+
+    use num_bigint::BigUint;
+    use num_traits::Zero;
+
+    #[test]
+    fn move_by_stays_inside_world() {
+
+        let world = World::new(Zero::zero(), 4).unwrap();
+        let mut cursor = super::Cursor::default();
+
+        cursor.move_by(-1, 2, &world);
+        assert_eq!(cursor.position(), (0, 2));
+
+        cursor.move_by(5, 5, &world);
+        assert_eq!(cursor.position(), (3, 3));
+    }
+
+    #[test]
+    fn stamp_overlays_pattern_at_cursor() {
+
+        /*
+            010
+            001
+            111
+        */
+
+        let glider = World::new(BigUint::parse_bytes(b"111100010",2).unwrap(), 3).unwrap();
+
+        let mut world = World::new(Zero::zero(), 5).unwrap();
+        let mut cursor = super::Cursor::default();
+
+        cursor.move_by(1, 2, &world);
+        cursor.stamp(&mut world, &glider);
+
+        assert_eq!(world.to_string(), "\
+┌──────────┐
+|          |
+|      ██  |
+|        ██|
+|    ██████|
+|          |
+└──────────┘
+");
+    }
+
+    #[test]
+    fn editor_handles_scripted_keys() {
+
+        use super::Key::*;
+
+        let mut editor = super::Editor::new(World::new(Zero::zero(), 5).unwrap());
+
+        // draw a vertical blinker in the middle column, then step it once
+        assert!(editor.handle_all(vec![Down, Right, Right, Toggle, Down, Toggle, Down, Toggle, Left, Right, Step]));
+
+        assert_eq!(editor.cursor().position(), (3, 2));
+        assert_eq!(editor.world().live_cells().collect::<Vec<_>>(), vec![(1, 2), (2, 2), (3, 2)]);
+
+        assert!(editor.handle(Play));
+        assert!(editor.is_playing());
+
+        // going back to the first generation makes the world stable, which pauses it
+        editor.tick();
+        assert!(editor.world().is_stable());
+        assert!(!editor.is_playing());
+
+        // keys after quitting are ignored
+        assert!(!editor.handle_all(vec![Toggle, Quit, Toggle]));
+        assert!(!editor.world().is_stable());
+        assert_eq!(editor.world().live_cells().collect::<Vec<_>>(), vec![(1, 2), (2, 2), (3, 2), (2, 3)]);
+
+        // a glider stamped under the cursor brings an empty, stable world back to life
+        let mut editor = super::Editor::new(World::new(Zero::zero(), 5).unwrap());
+
+        assert!(editor.handle_all(vec![Step, Right, Stamp]));
+        assert!(!editor.world().is_stable());
+        assert_eq!(editor.world().live_cells().collect::<Vec<_>>(), vec![(2, 0), (3, 1), (1, 2), (2, 2), (3, 2)]);
+    }
+}
@@ -1,195 +1,3523 @@
 use std::fmt;
+use std::collections::{ HashMap, VecDeque };
 
 use num_bigint::BigUint;
-use num_traits::One;
+use num_traits::{ One, Zero };
+use rand::{ Rng, SeedableRng, rngs::StdRng, seq::SliceRandom };
 
+use crate::rle;
+
+/// How cells are updated on each `advance`.
+///
+/// `Sync` is classic Life: every cell is computed from the previous generation.
+/// `Async` updates cells one at a time in place, so each cell sees the neighbours already updated
+/// during the same step. With a `Random` order the same state can have different successors,
+/// so reaching a recorded state again does not guarantee the world is actually cycling.
+/// `Margolus` replaces Conway's rule with a block cellular automaton: the grid is split into 2x2 blocks,
+/// offset by one cell on odd generations, and each block is replaced through `table`. Blocks are indexed
+/// by their cells as `nw = 1, ne = 2, sw = 4, se = 8`, and blocks crossing the edge are left untouched.
+/// When `table` is a permutation the rule is reversible and `advance_reverse` can step backwards.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UpdateMode {
+    #[default]
+    Sync,
+    Async { order: AsyncOrder },
+    Margolus { table: [u8; 16] }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AsyncOrder {
+    Sequential,
+    Reverse,
+    Random(u64)
+}
+
+/// Life-like rule in B/S notation: a dead cell comes alive when its live neighbour count is in `birth`,
+/// and a live cell survives when its count is in `survival`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rule {
+    pub birth: [bool; 9],
+    pub survival: [bool; 9]
+}
+
+impl Rule {
+
+    /// Parses a rule written as `B<digits>/S<digits>`, such as `B3/S23` or `B36/S23`.
+    pub fn parse(rule: &str) -> Result<Rule, WorldError> {
+
+        let (birth, survival) = rule.trim().split_once('/').ok_or(WorldError::InvalidRule("Rule must be written as B<digits>/S<digits>"))?;
+
+        let birth = birth.strip_prefix(['B', 'b']).ok_or(WorldError::InvalidRule("Rule must start with B"))?;
+        let survival = survival.strip_prefix(['S', 's']).ok_or(WorldError::InvalidRule("Survival counts must start with S"))?;
+
+        Ok(Rule { birth: Rule::parse_counts(birth)?, survival: Rule::parse_counts(survival)? })
+    }
+
+    fn parse_counts(digits: &str) -> Result<[bool; 9], WorldError> {
+
+        let mut counts = [false; 9];
+
+        for digit in digits.chars() {
+            match digit.to_digit(10) {
+                Some(count) if count < 9 => counts[count as usize] = true,
+                _ => return Err(WorldError::InvalidRule("Neighbour counts must be digits from 0 to 8"))
+            }
+        }
+
+        Ok(counts)
+    }
+}
+
+impl Default for Rule {
+
+    fn default() -> Rule {
+        Rule::parse("B3/S23").unwrap()
+    }
+}
+
+impl fmt::Display for Rule {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+
+        let digits = |counts: &[bool; 9]| -> String {
+            (0..9).filter(|&i| counts[i]).map(|i| char::from(b'0' + i as u8)).collect()
+        };
+
+        write!(f, "B{}/S{}", digits(&self.birth), digits(&self.survival))
+    }
+}
+
+/// With the `serde` feature, worlds serialize with their whole history, so a restored world
+/// keeps detecting cycles and advances exactly like the original. Boards are written as hex strings.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(into = "WorldState", try_from = "WorldState"))]
 pub struct World {
-    world_size: u8,
+    width: u8,
+    height: u8,
     world: BigUint,
-    states: Vec<BigUint>,
-    stable: bool
+    states: VecDeque<BigUint>,
+    // how many times each state appears in `states`
+    seen: HashMap<BigUint, usize>,
+    initial: BigUint,
+    max_history: Option<usize>,
+    // generations no longer recorded, evicted by `max_history` or preceding the last edit
+    dropped: usize,
+    stable: bool,
+    loop_start: Option<usize>,
+    update_mode: UpdateMode,
+    active_set: bool,
+    changed: Option<BigUint>,
+    wrap: bool,
+    rule: Rule
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub row: u8,
+    pub col: u8,
+    pub height: u8,
+    pub width: u8
+}
+
+/// Whether the world is still evolving, frozen, or cycling through states it already went through.
+/// An extinct world is a still life.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stability {
+    Active,
+    StillLife,
+    Oscillating { period: usize }
+}
+
+/// Reasons a world cannot be created, or two worlds combined.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WorldError {
+    SizeTooLarge { max: u8, got: u8 },
+    SizeZero,
+    SeedTooLarge { bits: u64, capacity: u64 },
+    ConflictingSizes,
+    ZeroHistory,
+    ZeroStripePeriod,
+    InvalidGrid(&'static str),
+    InvalidRule(&'static str),
+    DimensionMismatch,
+    PatternOutOfBounds,
+    NotReversible,
+    AtFirstGeneration
+}
+
+impl fmt::Display for WorldError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WorldError::SizeTooLarge { max, got } => write!(f, "world size {} exceeds the maximum of {}", got, max),
+            WorldError::SizeZero => write!(f, "world size must be greater than 0"),
+            WorldError::SeedTooLarge { bits, capacity } => write!(f, "seed needs {} bits but the world only holds {} cells", bits, capacity),
+            WorldError::ConflictingSizes => write!(f, "size and dimensions cannot both be set"),
+            WorldError::ZeroHistory => write!(f, "history must hold at least the current state"),
+            WorldError::ZeroStripePeriod => write!(f, "stripe period must be greater than 0"),
+            WorldError::InvalidGrid(reason) => write!(f, "{}", reason),
+            WorldError::InvalidRule(reason) => write!(f, "{}", reason),
+            WorldError::DimensionMismatch => write!(f, "worlds must have the same dimensions"),
+            WorldError::PatternOutOfBounds => write!(f, "pattern does not fit in the world at the given position"),
+            WorldError::NotReversible => write!(f, "only Margolus block rules that are permutations can run backwards"),
+            WorldError::AtFirstGeneration => write!(f, "already at the first generation")
+        }
+    }
+}
+
+impl std::error::Error for WorldError {}
+
+/// Cells that changed between two worlds, as `(x, y)` coordinates in reading order.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Diff {
+    pub born: Vec<(u16, u16)>,
+    pub died: Vec<(u16, u16)>
+}
+
+/// Glyphs used by `World::render` for every cell, each optionally wrapped in an ANSI color code such as `"32"` or `"1;31"`.
+/// Both glyphs should take the same number of columns on screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+    pub alive: String,
+    pub dead: String,
+    pub alive_color: Option<String>,
+    pub dead_color: Option<String>,
+    pub border: bool
+}
+
+impl Default for RenderOptions {
+
+    fn default() -> RenderOptions {
+        RenderOptions {
+            alive: String::from("██"),
+            dead: String::from("  "),
+            alive_color: None,
+            dead_color: None,
+            border: true
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct WorldBuilder {
+    seed: BigUint,
+    size: Option<u8>,
+    dimensions: Option<(u8, u8)>,
+    update_mode: UpdateMode,
+    expected_generations: usize,
+    max_history: Option<usize>,
+    active_set: bool,
+    wrap: bool,
+    rule: Rule
+}
+
+impl WorldBuilder {
+
+    pub fn new() -> WorldBuilder {
+        WorldBuilder::default()
+    }
+
+    pub fn seed(mut self, seed: BigUint) -> WorldBuilder {
+        self.seed = seed;
+        self
+    }
+
+    /// Makes the world a square. Cannot be combined with `dimensions`.
+    pub fn size(mut self, world_size: u8) -> WorldBuilder {
+        self.size = Some(world_size);
+        self
+    }
+
+    /// Makes the world a rectangle, as `World::new_rect` does. Cannot be combined with `size`.
+    pub fn dimensions(mut self, width: u8, height: u8) -> WorldBuilder {
+        self.dimensions = Some((width, height));
+        self
+    }
+
+    pub fn rule(mut self, rule: Rule) -> WorldBuilder {
+        self.rule = rule;
+        self
+    }
+
+    pub fn update_mode(mut self, update_mode: UpdateMode) -> WorldBuilder {
+        self.update_mode = update_mode;
+        self
+    }
+
+    /// Reserves room in the history and in the set of seen states for about `generations` states, avoiding reallocations on long runs.
+    pub fn expected_generations(mut self, generations: usize) -> WorldBuilder {
+        self.expected_generations = generations;
+        self
+    }
+
+    /// Only keeps the `max_history` most recent states instead of the whole run, bounding memory use.
+    /// Cycles are only detected against that window, so oscillators with a period longer than it are missed.
+    pub fn max_history(mut self, max_history: Option<usize>) -> WorldBuilder {
+        self.max_history = max_history;
+        self
+    }
+
+    /// Only reevaluates the cells that changed in the previous generation and their neighbours,
+    /// which is faster on mostly static worlds. Only applies to the `Sync` update mode.
+    pub fn active_set(mut self, active_set: bool) -> WorldBuilder {
+        self.active_set = active_set;
+        self
+    }
+
+    /// Makes the world a torus, as `World::new_wrapping` does.
+    pub fn wrapping(mut self, wrap: bool) -> WorldBuilder {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn build(self) -> Result<World, WorldError> {
+
+        if self.max_history == Some(0) {
+            return Err(WorldError::ZeroHistory);
+        }
+
+        let (width, height) = match (self.size, self.dimensions) {
+            (Some(_), Some(_)) => return Err(WorldError::ConflictingSizes),
+            (Some(world_size), None) => (world_size, world_size),
+            (None, Some(dimensions)) => dimensions,
+            (None, None) => return Err(WorldError::SizeZero)
+        };
+
+        let mut world = World::new_rect(self.seed, width, height)?;
+
+        world.update_mode = self.update_mode;
+        world.active_set = self.active_set;
+        world.wrap = self.wrap;
+        world.rule = self.rule;
+        world.max_history = self.max_history;
+
+        let expected = self.expected_generations.min(self.max_history.unwrap_or(usize::MAX));
+        world.states.reserve(expected);
+        world.seen.reserve(expected);
+
+        Ok(world)
+    }
 }
 
 impl World {
     const MAX_WORLD_SIZE: u8 = 50;
-    const UNDERPOPULATION_TRESHOLD: u8 = 2;
-    const OVERPOPULATION_TRESHOLD: u8 = 3;
-    const REPRODUCTION_TRIGGER: u8 = 3;
 
-    pub fn new<'a>(seed: BigUint, world_size: u8) -> Result<World, &'a str> {
+    pub fn new(seed: BigUint, world_size: u8) -> Result<World, WorldError> {
+        World::new_rect(seed, world_size, world_size)
+    }
+
+    /// Creates a `width` x `height` world. Seeds with bits set past the last cell are rejected with `SeedTooLarge`
+    /// rather than masked off, as those cells would never be shown.
+    pub fn new_rect(seed: BigUint, width: u8, height: u8) -> Result<World, WorldError> {
+
+
+        if width > World::MAX_WORLD_SIZE || height > World::MAX_WORLD_SIZE {
+            return Err(WorldError::SizeTooLarge { max: World::MAX_WORLD_SIZE, got: width.max(height) });
+        }
+
+        if width < 1 || height < 1 {
+            return Err(WorldError::SizeZero);
+        }
+
+        let capacity = width as u64 * height as u64;
+
+        if seed.bits() > capacity {
+            return Err(WorldError::SeedTooLarge { bits: seed.bits(), capacity });
+        }
+
+        Ok(World::from_state(seed, width, height))
+    }
+
+    /// Creates a world whose edges wrap around, like a torus: cells on the first column are neighbours
+    /// of the cells on the last one, and the same goes for the first and last rows.
+    pub fn new_wrapping(seed: BigUint, world_size: u8) -> Result<World, WorldError> {
+
+        let mut world = World::new(seed, world_size)?;
+        world.wrap = true;
+
+        Ok(world)
+    }
+
+    pub fn new_with_rule(seed: BigUint, world_size: u8, rule: Rule) -> Result<World, WorldError> {
+
+        let mut world = World::new(seed, world_size)?;
+        world.rule = rule;
+
+        Ok(world)
+    }
+
+    fn from_state(state: BigUint, width: u8, height: u8) -> World {
+        World {
+            world: state.clone(),
+            width,
+            height,
+            states: VecDeque::from(vec![state.clone()]),
+            seen: HashMap::from([(state.clone(), 1)]),
+            initial: state,
+            max_history: None,
+            dropped: 0,
+            stable: false,
+            loop_start: None,
+            update_mode: UpdateMode::Sync,
+            active_set: false,
+            changed: None,
+            wrap: false,
+            rule: Rule::default()
+        }
+    }
+
+    pub fn checkerboard(world_size: u8) -> Result<World, WorldError> {
+        World::from_fn(world_size, |row, col| (row + col).is_multiple_of(2))
+    }
 
+    pub fn stripes(world_size: u8, period: u8) -> Result<World, WorldError> {
 
-        if world_size > World::MAX_WORLD_SIZE {
-            return Err("World size exceeds maximum allowed.");
+        if period < 1 {
+            return Err(WorldError::ZeroStripePeriod);
         }
 
-        if world_size < 1 {
-            return Err("World size must be greater than 0");
+        World::from_fn(world_size, |row, _| (row / period as u16).is_multiple_of(2))
+    }
+
+    /// Creates a square world where each cell is alive with probability `density`.
+    /// Fails like `new` if `world_size` is not a valid world size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `density` is outside of `0.0..=1.0`.
+    pub fn random<R: Rng>(world_size: u8, density: f64, rng: &mut R) -> Result<World, WorldError> {
+
+        World::new(Zero::zero(), world_size)?;
+
+        let mut seed: BigUint = Zero::zero();
+
+        for i in 0..(world_size as u64).pow(2) {
+            if rng.gen_bool(density) { seed.set_bit(i, true); }
+        }
+
+        World::new(seed, world_size)
+    }
+
+    /// Parses a grid drawn as text, one line per row, where `#`, `O` and `1` are live cells
+    /// and `.`, spaces and `0` are dead ones. Rows shorter than the world are padded with dead cells.
+    pub fn from_ascii(grid: &str, world_size: u8) -> Result<World, WorldError> {
+
+        let size = world_size as usize;
+        let rows: Vec<&str> = grid.lines().map(|line| line.trim_end_matches('\r')).collect();
+
+        if rows.len() > size {
+            return Err(WorldError::InvalidGrid("grid has more rows than the world"));
         }
 
-        Ok(
-            World {
-                world: seed.clone(),
-                world_size: world_size,
-                states: vec![seed],
-                stable: false
+        let mut seed: BigUint = Zero::zero();
+
+        for (row, line) in rows.iter().enumerate() {
+
+            if line.chars().count() > size {
+                return Err(WorldError::InvalidGrid("grid row is longer than the world"));
             }
-        )
-        
+
+            for (col, cell) in line.chars().enumerate() {
+                match cell {
+                    '#' | 'O' | '1' => seed.set_bit((row * size + col) as u64, true),
+                    '.' | ' ' | '0' => (),
+                    _ => return Err(WorldError::InvalidGrid("invalid grid cell"))
+                }
+            }
+        }
+
+        World::new(seed, world_size)
+    }
+
+    /// Parses a macrocell file holding a single leaf node, where rows of `.` (dead) and `*` (alive)
+    /// are terminated by `$`, along with the rule given by its `#R` line. Files describing a hashed quadtree
+    /// are not supported yet.
+    pub fn from_macrocell(macrocell: &str) -> Result<World, WorldError> {
+
+        let mut lines = macrocell.lines().map(str::trim).filter(|line| !line.is_empty());
+
+        if !lines.next().is_some_and(|header| header.starts_with("[M2]")) {
+            return Err(WorldError::InvalidGrid("missing macrocell header"));
+        }
+
+        let (comments, nodes): (Vec<&str>, Vec<&str>) = lines.partition(|line| line.starts_with('#'));
+
+        let rule = match comments.iter().find_map(|line| line.strip_prefix("#R")) {
+            Some(rule) => Rule::parse(rule)?,
+            None => Rule::default()
+        };
+
+        let mut nodes = nodes.into_iter();
+
+        let leaf = nodes.next().ok_or(WorldError::InvalidGrid("macrocell file has no cells"))?;
+
+        if leaf.starts_with(|c: char| c.is_ascii_digit()) || nodes.next().is_some() {
+            return Err(WorldError::InvalidGrid("hashed macrocell trees are not supported"));
+        }
+
+        let mut cells = Vec::new();
+
+        for (row, line) in leaf.split_terminator('$').enumerate() {
+            for (col, cell) in line.chars().enumerate() {
+                match cell {
+                    '*' => cells.push((row, col)),
+                    '.' => (),
+                    _ => return Err(WorldError::InvalidGrid("invalid macrocell cell"))
+                }
+            }
+        }
+
+        let height = cells.iter().map(|&(row, _)| row + 1).max().unwrap_or(1).min(World::MAX_WORLD_SIZE as usize);
+        let width = cells.iter().map(|&(_, col)| col + 1).max().unwrap_or(1).min(World::MAX_WORLD_SIZE as usize);
+
+        let mut seed: BigUint = Zero::zero();
+
+        for (row, col) in cells.into_iter().filter(|&(row, col)| row < height && col < width) {
+            seed.set_bit((row * width + col) as u64, true);
+        }
+
+        let mut world = World::new_rect(seed, width as u8, height as u8)?;
+        world.rule = rule;
+
+        Ok(world)
+    }
+
+    fn from_fn<F: Fn(u16, u16) -> bool>(world_size: u8, alive: F) -> Result<World, WorldError> {
+
+        let size = world_size as u16;
+        let mut seed: BigUint = Zero::zero();
+
+        for i in 0..size.pow(2) {
+            if alive(i / size, i % size) { seed.set_bit(i.into(), true); }
+        }
+
+        World::new(seed, world_size)
+    }
+
+    /// Returns a copy of the current state surrounded by `margin` dead cells on every side,
+    /// starting a fresh history. The margin shrinks if the result would exceed the maximum world size.
+    pub fn with_margin(&self, margin: u8) -> World {
+
+        let pad = |side: u8| (side as u16 + 2 * margin as u16).min(World::MAX_WORLD_SIZE as u16) as u8;
+        let (width, height) = (pad(self.width), pad(self.height));
+
+        let mut padded = self.empty_like(width, height);
+        padded.overlay(self, (height - self.height) / 2, (width - self.width) / 2);
+
+        padded
+    }
+
+    /// Returns a copy of the current state rotated 90° clockwise, starting a fresh history.
+    /// The width and height of rectangular worlds are swapped.
+    pub fn rotate_90(&self) -> World {
+
+        let height = self.height as u16;
+
+        self.remapped(self.height, self.width, |row, col| (col, height - 1 - row))
+    }
+
+    /// Returns a copy of the current state mirrored left to right, starting a fresh history.
+    pub fn flip_horizontal(&self) -> World {
+
+        let width = self.width as u16;
+
+        self.remapped(self.width, self.height, |row, col| (row, width - 1 - col))
+    }
+
+    /// Returns a copy of the current state with every live cell moved `dx` columns right and `dy` rows down,
+    /// starting a fresh history. As with `translate`, cells moved past an edge are dropped unless the world wraps around.
+    pub fn translated(&self, dx: i32, dy: i32) -> World {
+
+        let (width, height) = (self.width as i32, self.height as i32);
+
+        let (d_row, d_col) = if self.wrap {
+            (dy.rem_euclid(height), dx.rem_euclid(width))
+        }
+        else {
+            (dy.clamp(-height, height), dx.clamp(-width, width))
+        };
+
+        let mut moved = self.empty_like(self.width, self.height);
+        moved.replace_state(self.shifted(d_row as i16, d_col as i16, self.wrap));
+
+        moved
+    }
+
+    fn empty_like(&self, width: u8, height: u8) -> World {
+
+        let mut world = World::from_state(Zero::zero(), width, height);
+        world.update_mode = self.update_mode;
+        world.active_set = self.active_set;
+        world.wrap = self.wrap;
+        world.rule = self.rule;
+
+        world
+    }
+
+    fn remapped<F: Fn(u16, u16) -> (u16, u16)>(&self, width: u8, height: u8, target: F) -> World {
+
+        let mut new_world: BigUint = Zero::zero();
+
+        for (x, y) in self.live_cells() {
+
+            let (row, col) = target(y, x);
+            new_world.set_bit((row * width as u16 + col).into(), true);
+        }
+
+        let mut world = self.empty_like(width, height);
+        world.replace_state(new_world);
+
+        world
     }
 
     pub fn is_stable(&self) -> bool {
         self.stable
     }
 
+    pub fn is_wrapping(&self) -> bool {
+        self.wrap
+    }
+
+    /// Returns the current generation as a number, which recreates it when passed back as a seed.
+    pub fn seed(&self) -> &BigUint {
+        &self.world
+    }
+
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    pub fn height(&self) -> u8 {
+        self.height
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        self.stable = false;
+        self.changed = None;
+    }
+
+    pub fn set_update_mode(&mut self, update_mode: UpdateMode) {
+        self.update_mode = update_mode;
+        self.stable = false;
+        self.changed = None;
+    }
+
     pub fn advance(&mut self) {
     
         if self.stable { return; }
 
-        let mut new_world: BigUint = self.world.clone();
+        let new_world = self.next_generation();
+
+        let changed = if self.active_set && self.update_mode == UpdateMode::Sync {
+            Some(&self.world ^ &new_world)
+        }
+        else {
+            None
+        };
+
+        self.commit_generation(new_world);
+        self.changed = changed;
+    }
 
-        let world_size = self.world_size as u16;
-        let one: BigUint = One::one();
+    /// Same as `advance`, but full synchronous steps are split into rows computed concurrently with rayon.
+    /// Other update modes fall back to `advance`.
+    #[cfg(feature = "parallel")]
+    pub fn advance_parallel(&mut self) {
+
+        if self.stable { return; }
+
+        if self.update_mode != UpdateMode::Sync {
+            self.advance();
+            return;
+        }
+
+        let new_world = self.sync_step_parallel();
+        let changed = if self.active_set { Some(&self.world ^ &new_world) } else { None };
+
+        self.commit_generation(new_world);
+        self.changed = changed;
+    }
+
+    /// Runs one step of the majority rule used to generate caves instead of Conway's rule:
+    /// a dead cell comes alive with more than `birth_limit` live neighbours
+    /// and a live cell dies with fewer than `death_limit`.
+    pub fn smooth(&mut self, birth_limit: u8, death_limit: u8) {
+
+        let mut new_world = self.world.clone();
+
+        for i in 0..self.area() {
 
-        for i in 0..world_size.pow(2) {
-    
             let cell_count = self.count_nearby_cells(i);
-            let current = self.get_cell(i);          
-    
-            if current == 1 
-                && (cell_count < World::UNDERPOPULATION_TRESHOLD || cell_count > World::OVERPOPULATION_TRESHOLD) {
-                new_world = BigUint::from(new_world ^ (&one << i));
+
+            if self.get_cell(i) == 1 {
+                new_world.set_bit(i.into(), cell_count >= death_limit);
             }
-            else if current == 0 && cell_count == World::REPRODUCTION_TRIGGER {
-                new_world = BigUint::from(new_world | (&one << i));
+            else {
+                new_world.set_bit(i.into(), cell_count > birth_limit);
             }
         }
-    
-        if (self.states).contains(&new_world) {    
-            self.stable = true;
-            return; 
-        }
-    
-        self.world = new_world;
 
-        self.states.push(self.world.clone());
+        self.stable = false;
+        self.commit_generation(new_world);
     }
 
-    fn count_nearby_cells(&self, index: u16) -> u8 {
-    
-        let mut cell_count = 0;
-        let world_size = self.world_size.into();
+    /// Once the world is stable, returns the generation the cycle started from
+    /// and the generation that repeated it.
+    pub fn cycle_info(&self) -> Option<(u64, u64)> {
+        self.cycle_start().map(|start| ((self.dropped + start) as u64, (self.dropped + self.states.len()) as u64))
+    }
 
-        let first_col = index % world_size == 0;
-        let last_col = (index + 1) % world_size == 0;
-        let first_row = index < world_size;
-        let last_row = index > (world_size.pow(2) - world_size);
-    
-        if !first_col { cell_count += self.get_cell(index - 1); }
-        if !last_col  { cell_count += self.get_cell(index + 1); }
-    
-        if !first_row { 
-            cell_count += self.get_cell(index - world_size); 
-            if !first_col { cell_count += self.get_cell(index - world_size - 1); }
-            if !last_col  { cell_count += self.get_cell(index - world_size + 1); }
+    /// Tells a still life, whose next generation is the current one, from a world cycling with a longer period.
+    pub fn stability(&self) -> Stability {
+        match self.period() {
+            None => Stability::Active,
+            Some(1) => Stability::StillLife,
+            Some(period) => Stability::Oscillating { period }
         }
-    
-        if !last_row  { 
-            cell_count += self.get_cell(index + world_size); 
-            if !first_col { cell_count += self.get_cell(index + world_size - 1); }
-            if !last_col  { cell_count += self.get_cell(index + world_size + 1); }
+    }
+
+    /// Once the world is stable, returns the length of the cycle it settled into:
+    /// 1 for a still life, 2 for a blinker and so on.
+    pub fn period(&self) -> Option<usize> {
+        self.cycle_start().map(|start| self.states.len() - start)
+    }
+
+    /// Advances until generation `max_gen` or until the world is stable, returning the generation
+    /// and a snapshot of every state, the current one included, for which `filter` returns true.
+    pub fn collect_matching<F: Fn(&World) -> bool>(&mut self, max_gen: u64, filter: F) -> Vec<(u64, World)> {
+
+        let mut matching = Vec::new();
+
+        loop {
+
+            let generation = self.generation() as u64;
+
+            if filter(self) {
+                matching.push((generation, self.clone()));
+            }
+
+            if generation >= max_gen { break; }
+
+            self.advance();
+
+            if self.stable { break; }
         }
 
-        cell_count
-    }    
+        matching
+    }
+
+    /// Returns true if the world has settled into a cycle in which no cell stays alive
+    /// for two consecutive generations.
+    pub fn is_phoenix(&self) -> bool {
+
+        let cycle: Vec<&BigUint> = match self.cycle_start() {
+            Some(start) => self.states.range(start..).collect(),
+            None => return false
+        };
+
+        if self.world.is_zero() { return false; }
+
+        cycle.iter()
+            .zip(cycle.iter().cycle().skip(1))
+            .all(|(&current, &next)| (current & next).is_zero())
+    }
+
+    /// Runs a copy of the world for up to `max_gen` generations and returns true if it settles
+    /// into a cycle, but only after at least `min_lifespan` generations from the current one.
+    pub fn is_methuselah(&self, min_lifespan: u64, max_gen: u64) -> bool {
+
+        let mut world = self.clone();
+        let start = world.generation() as u64;
+
+        for _ in 0..max_gen {
+            if world.stable { break; }
+            world.advance();
+        }
+
+        match world.cycle_info() {
+            Some((cycle_start, _)) => cycle_start.saturating_sub(start) >= min_lifespan,
+            None => false
+        }
+    }
+
+    /// Goes back to the previous generation, dropping the current one from the history.
+    /// Returns `false`, leaving the world untouched, if it is still at its initial state.
+    pub fn step_back(&mut self) -> bool {
+
+        if self.states.len() < 2 { return false; }
+
+        self.pop_state();
+
+        self.world = self.states[self.states.len() - 1].clone();
+        self.stable = false;
+        self.loop_start = None;
+        self.changed = None;
+
+        true
+    }
+
+    /// Rewinds to the most recent recorded state that differs from the current one,
+    /// skipping repeats of it. Returns `false`, leaving the world untouched, if there is none.
+    pub fn rewind_to_unique(&mut self) -> bool {
+
+        let last = self.states.len() - 1;
+
+        match self.states.range(..last).rposition(|state| *state != self.world) {
+            Some(position) => {
+
+                while self.states.len() > position + 1 { self.pop_state(); }
+
+                self.world = self.states[position].clone();
+                self.stable = false;
+                self.loop_start = None;
+                self.changed = None;
+
+                true
+            },
+            None => false
+        }
+    }
+
+    /// Steps back one generation by applying the inverse of a reversible `Margolus` block rule,
+    /// computing the previous state from the current one instead of reading it from the history.
+    pub fn advance_reverse(&mut self) -> Result<(), WorldError> {
+
+        let table = match self.update_mode {
+            UpdateMode::Margolus { table } => table,
+            _ => return Err(WorldError::NotReversible)
+        };
+
+        let mut inverse = [None; 16];
+
+        for (block, &next) in table.iter().enumerate() {
+
+            match inverse.get_mut(next as usize) {
+                Some(slot @ None) => *slot = Some(block as u8),
+                _ => return Err(WorldError::NotReversible)
+            }
+        }
+
+        if self.generation() == 0 {
+            return Err(WorldError::AtFirstGeneration);
+        }
+
+        let inverse = inverse.map(|block| block.unwrap());
+        let previous = self.margolus_step(&inverse, self.generation() - 1);
+
+        // the previous state replaces the current one, which past the start of a bounded history
+        // is also the oldest recorded state
+        self.pop_state();
+
+        if self.states.is_empty() {
+            self.dropped -= 1;
+        }
+        else {
+            self.pop_state();
+        }
+
+        self.world = previous;
+        self.states.push_back(self.world.clone());
+        self.remember(self.world.clone());
+        self.stable = false;
+        self.loop_start = None;
+        self.changed = None;
+
+        if self.generation() == 0 {
+            self.initial = self.world.clone();
+        }
+
+        Ok(())
+    }
+
+    /// Goes back to the first generation, forgetting the whole history, even the part already dropped
+    /// because of `max_history`. Edits made during the first generation are kept.
+    pub fn reset(&mut self) {
+
+        self.world = self.initial.clone();
+        self.states = VecDeque::from(vec![self.initial.clone()]);
+        self.seen = HashMap::from([(self.initial.clone(), 1)]);
+        self.dropped = 0;
+        self.stable = false;
+        self.loop_start = None;
+        self.changed = None;
+    }
+
+    /// Moves every live cell by the given offset.
+    /// Unless the world wraps around, cells moved past an edge are dropped.
+    pub fn translate(&mut self, d_row: i8, d_col: i8) {
+
+        let new_world = self.shifted(d_row.into(), d_col.into(), self.wrap);
+
+        self.replace_state(new_world);
+    }
+
+    /// Returns the smallest vertical and horizontal periods under which the world, seen as a torus,
+    /// repeats itself, such as `(2, 1)` for alternating horizontal stripes. An axis without a shorter period
+    /// reports the full height or width. Returns `None` unless at least one period is smaller than the world,
+    /// or for an empty world.
+    pub fn is_agar(&self) -> Option<(u8, u8)> {
+
+        if self.world.is_zero() { return None; }
+
+        let (width, height) = (self.width as i16, self.height as i16);
+
+        let row_period = (1..height).find(|&d_row| self.shifted(d_row, 0, true) == self.world).unwrap_or(height);
+        let col_period = (1..width).find(|&d_col| self.shifted(0, d_col, true) == self.world).unwrap_or(width);
+
+        if row_period < height || col_period < width {
+            Some((row_period as u8, col_period as u8))
+        }
+        else {
+            None
+        }
+    }
+
+    /// Returns the cells that come alive and the ones that die going from this world to `other`,
+    /// usually a copy taken before `advance` diffed against the advanced world.
+    pub fn diff(&self, other: &World) -> Result<Diff, WorldError> {
+
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(WorldError::DimensionMismatch);
+        }
+
+        let width = self.width as u64;
+        let changed = &self.world ^ &other.world;
+
+        let mut diff = Diff::default();
+
+        for index in (0..self.area() as u64).filter(|&index| changed.bit(index)) {
+
+            let cell = ((index % width) as u16, (index / width) as u16);
+
+            if other.world.bit(index) { diff.born.push(cell); } else { diff.died.push(cell); }
+        }
+
+        Ok(diff)
+    }
+
+    /// Counts the live cells inside the world, ignoring any bit set past its last cell.
+    pub fn population(&self) -> u64 {
+
+        let grid: BigUint = (BigUint::one() << self.area()) - 1u8;
+
+        (&self.world & grid).count_ones()
+    }
+
+    pub fn is_extinct(&self) -> bool {
+        self.population() == 0
+    }
+
+    /// Returns whether the cell at column `x` and row `y` is alive.
+    /// Panics if the cell lies outside the world.
+    pub fn cell(&self, x: u16, y: u16) -> bool {
+        self.get_cell(self.cell_index(x, y)) == 1
+    }
+
+    /// Makes the cell at column `x` and row `y` alive or dead. The world is no longer considered stable
+    /// and its history restarts from the edited state, as its future does not follow from the earlier generations anymore.
+    /// Panics if the cell lies outside the world.
+    pub fn set_cell(&mut self, x: u16, y: u16, alive: bool) {
+
+        let index = self.cell_index(x, y);
+        let mut new_world = self.world.clone();
+        new_world.set_bit(index.into(), alive);
+
+        self.replace_state(new_world);
+    }
+
+    /// Flips the cell at column `x` and row `y`, as `set_cell` does.
+    pub fn toggle_cell(&mut self, x: u16, y: u16) {
+        self.set_cell(x, y, !self.cell(x, y));
+    }
+
+    fn cell_index(&self, x: u16, y: u16) -> u16 {
+
+        assert!(x < self.width as u16 && y < self.height as u16,
+            "cell ({}, {}) is outside of the {} x {} world", x, y, self.width, self.height);
+
+        y * self.width as u16 + x
+    }
+
+    /// Iterates over the `(x, y)` coordinates of every live cell, in reading order.
+    pub fn live_cells(&self) -> impl Iterator<Item = (u16, u16)> + '_ {
+
+        let width = self.width as u16;
+
+        (0..self.area())
+            .filter(move |&i| self.get_cell(i) == 1)
+            .map(move |i| (i % width, i / width))
+    }
+
+    /// Returns whether the cell will be alive in the next generation under the synchronous rule,
+    /// without advancing the world, or `None` if it lies outside the world.
+    pub fn next_cell_state(&self, row: u8, col: u8) -> Option<bool> {
+        self.index(row, col).map(|index| self.next_cell(index))
+    }
+
+    pub fn history_len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Roughly estimates the memory held by the world, counting the digits of every recorded state
+    /// and of their copies kept to look up repeated states, with one control byte per slot of that map.
+    pub fn estimated_memory_bytes(&self) -> usize {
+
+        let digits_bytes = |state: &BigUint| state.bits().div_ceil(64) as usize * 8;
+
+        std::mem::size_of::<World>()
+            + digits_bytes(&self.world)
+            + self.states.capacity() * std::mem::size_of::<BigUint>()
+            + self.states.iter().map(digits_bytes).sum::<usize>()
+            + self.seen.capacity() * (std::mem::size_of::<(BigUint, usize)>() + 1)
+            + self.seen.keys().map(digits_bytes).sum::<usize>()
+    }
+
+    /// Hashes every recorded generation into a single value, so two runs only share it
+    /// if they went through the same states. The hash is 64-bit FNV-1a over the dimensions and the little-endian
+    /// bytes of each state, each preceded by its length, so it stays the same across Rust and dependency versions.
+    pub fn trajectory_hash(&self) -> u64 {
+
+        const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const PRIME: u64 = 0x100000001b3;
+
+        let fold = |hash: u64, byte: &u8| (hash ^ *byte as u64).wrapping_mul(PRIME);
+
+        let mut hash = [self.width, self.height].iter().fold(OFFSET_BASIS, fold);
+
+        for state in &self.states {
+
+            let bytes = state.to_bytes_le();
+
+            hash = (bytes.len() as u64).to_le_bytes().iter().fold(hash, fold);
+            hash = bytes.iter().fold(hash, fold);
+        }
+
+        hash
+    }
+
+    /// Returns the position and age, in generations, of the live cell that has been alive the longest.
+    /// Ties go to the first cell in reading order. Returns `None` for an empty world.
+    pub fn oldest_cell(&self) -> Option<(u8, u8, u32)> {
+
+        let width = self.width as u16;
+
+        (0..self.area())
+            .filter(|&i| self.get_cell(i) == 1)
+            .map(|i| ((i / width) as u8, (i % width) as u8, self.cell_age(i)))
+            .rev()
+            .max_by_key(|&(_, _, age)| age)
+    }
+
+    /// Returns the coordinates of the cells that changed during the last recorded step.
+    pub fn changed_cells(&self) -> Vec<(u8, u8)> {
+
+        if self.states.len() < 2 { return Vec::new(); }
+
+        let changed = &self.states[self.states.len() - 2] ^ &self.world;
+        let width = self.width as u64;
+
+        (0..self.area() as u64)
+            .filter(|&i| changed.bit(i))
+            .map(|i| ((i / width) as u8, (i % width) as u8))
+            .collect()
+    }
+
+    /// Returns the fraction of cells that changed during the last recorded step.
+    pub fn temperature(&self) -> f64 {
+        self.changed_cells().len() as f64 / self.area() as f64
+    }
+
+    /// Returns the most recent generation in which the cell differed from the previous one,
+    /// or `None` if it never changed or lies outside the world.
+    pub fn cell_last_changed(&self, row: u8, col: u8) -> Option<u64> {
+
+        let index = self.index(row, col)? as u64;
+
+        (1..self.states.len())
+            .rev()
+            .find(|&generation| self.states[generation].bit(index) != self.states[generation - 1].bit(index))
+            .map(|generation| (self.dropped + generation) as u64)
+    }
+
+    /// Renders the given row across every recorded generation as a binary PGM image,
+    /// one generation per line from top to bottom, live cells in black.
+    pub fn spacetime_pgm(&self, row: u8, scale: u32) -> Vec<u8> {
+
+        assert!(row < self.height, "row {} is outside of the world", row);
+
+        let width = self.width as u32 * scale;
+        let height = self.states.len() as u32 * scale;
+
+        let mut image = format!("P5\n{} {}\n255\n", width, height).into_bytes();
+
+        for state in &self.states {
+
+            let line: Vec<u8> = (0..self.width)
+                .map(|col| state.bit((row as u16 * self.width as u16 + col as u16).into()))
+                .flat_map(|alive| vec![if alive {0} else {255}; scale as usize])
+                .collect();
+
+            for _ in 0..scale {
+                image.extend_from_slice(&line);
+            }
+        }
+
+        image
+    }
+
+    /// Once the world is stable, returns the fraction of live cells belonging to still lifes
+    /// rather than oscillators. Returns `None` while the world is active or when it is empty.
+    pub fn still_life_fraction(&self) -> Option<f64> {
+
+        if !self.stable || self.world.is_zero() { return None; }
+
+        let still_cells: u64 = self.components()
+            .iter()
+            .filter(|component| self.component_period(component) == Some(1))
+            .map(|component| component.count_ones())
+            .sum();
+
+        Some(still_cells as f64 / self.population() as f64)
+    }
+
+    /// Once the world is stable, counts how many connected objects oscillate with each period
+    /// (period 1 being still lifes). Objects that only persist through interaction with others are left out.
+    pub fn period_census(&self) -> Option<HashMap<u64, u32>> {
+
+        if !self.stable { return None; }
+
+        let mut census = HashMap::new();
+
+        for period in self.components().iter().filter_map(|component| self.component_period(component)) {
+            *census.entry(period).or_insert(0) += 1;
+        }
+
+        Some(census)
+    }
+
+    /// Returns every connected object cropped to its bounding box, encoded as RLE.
+    pub fn export_components(&self) -> Vec<(BoundingBox, String)> {
+
+        let world_width = self.width as u16;
+
+        self.components()
+            .iter()
+            .map(|component| {
+
+                let cells: Vec<(u16, u16)> = (0..self.area())
+                    .filter(|&i| component.bit(i.into()))
+                    .map(|i| (i / world_width, i % world_width))
+                    .collect();
+
+                let row = cells.iter().map(|&(row, _)| row).min().unwrap();
+                let col = cells.iter().map(|&(_, col)| col).min().unwrap();
+                let height = cells.iter().map(|&(row, _)| row).max().unwrap() - row + 1;
+                let width = cells.iter().map(|&(_, col)| col).max().unwrap() - col + 1;
+
+                let bounding_box = BoundingBox { row: row as u8, col: col as u8, height: height as u8, width: width as u8 };
+                let encoded = rle::encode(width, height, &self.rule, |r, c| component.bit(((row + r) * world_width + col + c).into()));
+
+                (bounding_box, encoded)
+            })
+            .collect()
+    }
+
+    /// Crops the live cells to their bounding box and returns the smallest `(height, width, cells)`
+    /// among its 4 rotations and their reflections, so every orientation of an object yields the same value.
+    pub fn canonical_symmetric(&self) -> (u8, u8, BigUint) {
+
+        let cells = self.live_coordinates();
+
+        if cells.is_empty() { return (0, 0, Zero::zero()); }
+
+        let min_row = cells.iter().map(|&(row, _)| row).min().unwrap();
+        let min_col = cells.iter().map(|&(_, col)| col).min().unwrap();
+        let max_row = cells.iter().map(|&(row, _)| row).max().unwrap();
+        let max_col = cells.iter().map(|&(_, col)| col).max().unwrap();
+
+        let mut cells: Vec<(u16, u16)> = cells.iter().map(|&(row, col)| (row - min_row, col - min_col)).collect();
+        let (mut height, mut width) = (max_row - min_row + 1, max_col - min_col + 1);
+
+        let mut candidates = Vec::with_capacity(8);
+
+        for _ in 0..4 {
+
+            // rotate 90° clockwise
+            cells = cells.iter().map(|&(row, col)| (col, height - 1 - row)).collect();
+            std::mem::swap(&mut height, &mut width);
+
+            let reflected: Vec<(u16, u16)> = cells.iter().map(|&(row, col)| (row, width - 1 - col)).collect();
+
+            for transformed in [&cells, &reflected] {
+
+                let mut packed: BigUint = Zero::zero();
+                for &(row, col) in transformed {
+                    packed.set_bit((row * width + col).into(), true);
+                }
+
+                candidates.push((height as u8, width as u8, packed));
+            }
+        }
+
+        candidates.into_iter().min().unwrap()
+    }
+
+    /// Renders the world as `Display` does, showing where the live cells of `other` would land
+    /// if overlaid at the given position as ghost cells, without modifying the world.
+    pub fn preview_overlay(&self, other: &World, row: u8, col: u8) -> String {
+
+        let mut ghost = World::from_state(Zero::zero(), self.width, self.height);
+        ghost.overlay(other, row, col);
+
+        let preview = self.render_grid(|i| {
+            match (self.get_cell(i), ghost.get_cell(i)) {
+                (1, _) => "██",
+                (_, 1) => "░░",
+                _ => "  "
+            }
+        });
+
+        format!("{}\n", preview)
+    }
+
+    /// Renders the world with the given glyphs, colors and border. `Display` uses the default options.
+    pub fn render(&self, options: &RenderOptions) -> String {
+
+        let paint = |glyph: &str, color: &Option<String>| match color {
+            Some(code) => format!("\x1B[{}m{}\x1B[0m", code, glyph),
+            None => glyph.to_string()
+        };
+
+        let alive = paint(&options.alive, &options.alive_color);
+        let dead = paint(&options.dead, &options.dead_color);
+
+        self.render_rows(|i| if self.get_cell(i) == 1 { &alive } else { &dead }, options.alive.chars().count(), options.border)
+    }
+
+    /// Renders the world as `Display` does, showing the number of live neighbours of every cell
+    /// instead of its state. Live cells are marked with a `*` before their count.
+    pub fn render_counts(&self) -> String {
+
+        let counts = self.render_grid(|i| {
+            let marker = if self.get_cell(i) == 1 { '*' } else { ' ' };
+            format!("{}{}", marker, self.count_nearby_cells(i))
+        });
+
+        format!("{}\n", counts)
+    }
+
+    /// Renders the world as a tightly packed RGBA buffer, each cell being `cell_px` pixels wide.
+    /// Returns the image width, height and pixel data.
+    pub fn render_rgba(&self, cell_px: u32, live: [u8; 4], dead: [u8; 4]) -> (u32, u32, Vec<u8>) {
+
+        let (width, height, pixels) = self.render_cells(cell_px, live, dead);
+
+        (width, height, pixels.concat())
+    }
+
+    /// Renders the world as palette indices, each cell being `cell_px` pixels wide.
+    /// Returns the image width, height, index buffer and palette (dead cells in white, live cells in black).
+    pub fn render_indexed(&self, cell_px: u32) -> (u32, u32, Vec<u8>, Vec<[u8; 3]>) {
+
+        let palette = vec![[255, 255, 255], [0, 0, 0]];
+        let (width, height, indices) = self.render_cells(cell_px, 1, 0);
+
+        (width, height, indices, palette)
+    }
+
+    /// Saves the world as a PNG image with live cells as black `cell_px` x `cell_px` squares on a white background.
+    #[cfg(feature = "image")]
+    pub fn save_png(&self, path: &std::path::Path, cell_px: u32) -> std::io::Result<()> {
+
+        let (width, height, pixels) = self.render_cells(cell_px, 0u8, 255u8);
+
+        image::save_buffer(path, &pixels, width, height, image::ExtendedColorType::L8).map_err(std::io::Error::other)
+    }
+
+    /// Records a run as a looping GIF, rendered as `save_png` does, starting from `initial` and advancing
+    /// until the world is stable or `max_frames` generations are recorded. Each frame lasts `frame_delay_ms`.
+    #[cfg(feature = "image")]
+    pub fn record_gif(initial: World, path: &std::path::Path, cell_px: u32, frame_delay_ms: u16, max_frames: usize) -> std::io::Result<()> {
+
+        use image::{ Delay, Frame, RgbaImage, codecs::gif::{ GifEncoder, Repeat } };
+
+        let mut encoder = GifEncoder::new(std::fs::File::create(path)?);
+        encoder.set_repeat(Repeat::Infinite).map_err(std::io::Error::other)?;
+
+        let mut world = initial;
+        let delay = Delay::from_numer_denom_ms(frame_delay_ms.into(), 1);
+
+        for _ in 0..max_frames {
+
+            let (width, height, pixels) = world.render_rgba(cell_px, [0, 0, 0, 255], [255, 255, 255, 255]);
+            let image = RgbaImage::from_raw(width, height, pixels).unwrap();
+
+            encoder.encode_frame(Frame::from_parts(image, 0, 0, delay)).map_err(std::io::Error::other)?;
+
+            world.advance();
+            if world.is_stable() { break; }
+        }
+
+        Ok(())
+    }
+
+    /// Serializes the world and its history, see `from_json` to restore it.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<World, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Builds a self-contained HTML page animating every recorded generation,
+    /// advancing one frame every `interval_ms` milliseconds, with a play/pause button.
+    pub fn to_html(&self, interval_ms: u64) -> String {
+
+        let frames: Vec<String> = self.states.iter()
+            .map(|state| {
+                let cells: String = (0..self.area() as u64)
+                    .map(|i| if state.bit(i) {'1'} else {'0'})
+                    .collect();
+                format!("    \"{}\"", cells)
+            })
+            .collect();
+
+        format!(r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Conway's Game of life</title>
+</head>
+<body>
+<canvas id="world" width="{canvas_width}" height="{canvas_height}"></canvas>
+<p><button id="toggle">Pause</button> Generation <span id="generation">0</span></p>
+<script>
+const width = {width};
+const height = {height};
+const cell = 10;
+const frames = [
+{frames}
+];
+const context = document.getElementById("world").getContext("2d");
+const toggle = document.getElementById("toggle");
+let frame = 0;
+let playing = true;
+function draw() {{
+    context.fillStyle = "white";
+    context.fillRect(0, 0, width * cell, height * cell);
+    context.fillStyle = "black";
+    for (let i = 0; i < width * height; i++) {{
+        if (frames[frame][i] === "1") {{
+            context.fillRect((i % width) * cell, Math.floor(i / width) * cell, cell, cell);
+        }}
+    }}
+    document.getElementById("generation").textContent = frame;
+}}
+toggle.addEventListener("click", () => {{
+    playing = !playing;
+    toggle.textContent = playing ? "Pause" : "Play";
+}});
+setInterval(() => {{
+    if (playing) {{
+        frame = (frame + 1) % frames.length;
+        draw();
+    }}
+}}, {interval});
+draw();
+</script>
+</body>
+</html>
+"#, canvas_width = self.width as u32 * 10, canvas_height = self.height as u32 * 10,
+            width = self.width, height = self.height, frames = frames.join(",\n"), interval = interval_ms)
+    }
+
+    /// Computes the per-generation mean and standard deviation of several population histories.
+    /// Shorter histories are treated as holding their final population once they end.
+    pub fn ensemble_stats(histories: &[Vec<u64>]) -> Vec<(f64, f64)> {
+
+        let histories: Vec<&Vec<u64>> = histories.iter().filter(|history| !history.is_empty()).collect();
+        let generations = histories.iter().map(|history| history.len()).max().unwrap_or(0);
+        let runs = histories.len() as f64;
+
+        (0..generations)
+            .map(|generation| {
+
+                let populations: Vec<f64> = histories.iter()
+                    .map(|history| history[generation.min(history.len() - 1)] as f64)
+                    .collect();
+
+                let mean = populations.iter().sum::<f64>() / runs;
+                let variance = populations.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / runs;
+
+                (mean, variance.sqrt())
+            })
+            .collect()
+    }
+
+    /// Copies the live cells of `other` into the world with its top-left corner at the given position.
+    /// Cells falling outside the world are dropped.
+    pub fn overlay(&mut self, other: &World, row: u8, col: u8) {
+
+        let mut new_world = self.world.clone();
+
+        for i in 0..other.area() {
+
+            if other.get_cell(i) == 0 { continue; }
+
+            let target_row = row as u16 + i / other.width as u16;
+            let target_col = col as u16 + i % other.width as u16;
+
+            if target_row < self.height as u16 && target_col < self.width as u16 {
+                new_world.set_bit((target_row * self.width as u16 + target_col).into(), true);
+            }
+        }
+
+        self.replace_state(new_world);
+    }
+
+    /// Panics if the internal state is inconsistent: the current world must be the last recorded state
+    /// and no live cell may lie outside the grid. Checking the whole history only happens in debug builds.
+    pub fn check_invariants(&self) {
+
+        let capacity = self.area() as u64;
+
+        assert!((1..=World::MAX_WORLD_SIZE).contains(&self.width), "world width out of range");
+        assert!((1..=World::MAX_WORLD_SIZE).contains(&self.height), "world height out of range");
+        assert_eq!(self.states.back(), Some(&self.world), "last recorded state differs from the world");
+        assert!(self.dropped > 0 || self.states.front() == Some(&self.initial), "first recorded state differs from the initial one");
+        assert!(self.max_history.is_none_or(|max| self.states.len() <= max), "history exceeds its maximum length");
+        assert!(self.world.bits() <= capacity, "live cells outside of the grid");
+
+        #[cfg(debug_assertions)]
+        for state in &self.states {
+            assert!(state.bits() <= capacity, "recorded state has cells outside of the grid");
+            assert_eq!(self.seen.get(state), Some(&self.states.iter().filter(|other| *other == state).count()),
+                "seen states out of sync with the history");
+        }
+
+        assert_eq!(self.seen.values().sum::<usize>(), self.states.len(), "seen states out of sync with the history");
+    }
+
+    fn commit_generation(&mut self, new_world: BigUint) {
+    
+        if self.seen.contains_key(&new_world) {
+            self.stable = true;
+            self.loop_start = self.states.iter().position(|state| *state == new_world);
+            return; 
+        }
+    
+        self.world = new_world;
+        self.changed = None;
+
+        self.states.push_back(self.world.clone());
+        self.remember(self.world.clone());
+
+        if self.max_history.is_some_and(|max| self.states.len() > max) {
+
+            if let Some(oldest) = self.states.pop_front() { self.forget(&oldest); }
+
+            self.dropped += 1;
+        }
+    }
+
+    fn pop_state(&mut self) {
+        if let Some(last) = self.states.pop_back() { self.forget(&last); }
+    }
+
+    fn remember(&mut self, state: BigUint) {
+        *self.seen.entry(state).or_insert(0) += 1;
+    }
+
+    fn forget(&mut self, state: &BigUint) {
+
+        if let Some(count) = self.seen.get_mut(state) {
+
+            *count -= 1;
+
+            if *count == 0 { self.seen.remove(state); }
+        }
+    }
+
+    fn shifted(&self, d_row: i16, d_col: i16, wrap: bool) -> BigUint {
+
+        let (width, height) = (self.width as i16, self.height as i16);
+        let mut new_world: BigUint = Zero::zero();
+
+        for i in 0..self.area() {
+
+            if self.get_cell(i) == 0 { continue; }
+
+            let mut row = i as i16 / width + d_row;
+            let mut col = i as i16 % width + d_col;
+
+            if wrap {
+                row = row.rem_euclid(height);
+                col = col.rem_euclid(width);
+            }
+
+            if (0..height).contains(&row) && (0..width).contains(&col) {
+                new_world.set_bit((row * width + col) as u64, true);
+            }
+        }
+
+        new_world
+    }
+
+    // an edited world no longer follows from its history, so a new one starts from it,
+    // counting the generations before the edit as dropped
+    fn replace_state(&mut self, new_world: BigUint) {
+
+        self.dropped = self.generation();
+        self.world = new_world;
+        self.states = VecDeque::from(vec![self.world.clone()]);
+        self.seen = HashMap::from([(self.world.clone(), 1)]);
+        self.stable = false;
+        self.loop_start = None;
+        self.changed = None;
+
+        if self.dropped == 0 {
+            self.initial = self.world.clone();
+        }
+    }
+
+    fn next_generation(&self) -> BigUint {
+
+        let order = match self.update_mode {
+            UpdateMode::Sync => {
+
+                let changed = match &self.changed {
+                    Some(changed) => changed,
+                    None => return self.sync_step()
+                };
+
+                let mut new_world: BigUint = self.world.clone();
+
+                for i in self.active_cells(changed) {
+                    new_world.set_bit(i.into(), self.next_cell(i));
+                }
+
+                return new_world;
+            },
+            UpdateMode::Async { order } => order,
+            UpdateMode::Margolus { table } => return self.margolus_step(&table, self.generation())
+        };
+
+        let mut indices: Vec<u16> = (0..self.area()).collect();
+
+        match order {
+            AsyncOrder::Sequential => (),
+            AsyncOrder::Reverse => indices.reverse(),
+            AsyncOrder::Random(seed) => {
+                let mut rng = StdRng::seed_from_u64(seed.wrapping_add(self.generation() as u64 + 1));
+                indices.shuffle(&mut rng);
+            }
+        }
+
+        let mut scratch = World::from_state(self.world.clone(), self.width, self.height);
+        scratch.wrap = self.wrap;
+        scratch.rule = self.rule;
+
+        for i in indices {
+            let alive = scratch.next_cell(i);
+            scratch.world.set_bit(i.into(), alive);
+        }
+
+        scratch.world
+    }
+
+    /// Computes the next synchronous generation of the whole grid on plain integers:
+    /// the cells are unpacked once, each row is computed from its neighbours' counts
+    /// and the result is packed back. Bits past the last cell are carried over untouched.
+    fn sync_step(&self) -> BigUint {
+
+        let cells = self.unpacked_cells();
+
+        self.pack_cells((0..self.height as i32).flat_map(|row| self.next_row(&cells, row)))
+    }
+
+    /// Same as `sync_step`, with the rows computed concurrently.
+    #[cfg(feature = "parallel")]
+    fn sync_step_parallel(&self) -> BigUint {
+
+        use rayon::prelude::*;
+
+        let cells = self.unpacked_cells();
+        let rows: Vec<Vec<bool>> = (0..self.height as i32).into_par_iter().map(|row| self.next_row(&cells, row)).collect();
+
+        self.pack_cells(rows.into_iter().flatten())
+    }
+
+    fn unpacked_cells(&self) -> Vec<bool> {
+
+        let area = self.area() as usize;
+        let mut cells = vec![false; area];
+
+        for (digit, mut bits) in self.world.iter_u64_digits().enumerate() {
+            while bits != 0 {
+
+                let index = digit * 64 + bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+
+                if index < area { cells[index] = true; }
+            }
+        }
+
+        cells
+    }
+
+    fn next_row(&self, cells: &[bool], row: i32) -> Vec<bool> {
+
+        let (width, height) = (self.width as i32, self.height as i32);
+
+        (0..width).map(|col| {
+
+            let mut count = 0;
+
+            for d_row in -1..=1 {
+                for d_col in -1..=1 {
+
+                    if (d_row, d_col) == (0, 0) { continue; }
+
+                    let (mut r, mut c) = (row + d_row, col + d_col);
+
+                    if self.wrap {
+                        r = r.rem_euclid(height);
+                        c = c.rem_euclid(width);
+                    }
+                    else if !(0..height).contains(&r) || !(0..width).contains(&c) {
+                        continue;
+                    }
+
+                    if cells[(r * width + c) as usize] { count += 1; }
+                }
+            }
+
+            if cells[(row * width + col) as usize] { self.rule.survival[count] } else { self.rule.birth[count] }
+        }).collect()
+    }
+
+    fn pack_cells<I: Iterator<Item = bool>>(&self, cells: I) -> BigUint {
+
+        let area = self.area() as usize;
+        let mut digits = vec![0u32; area.div_ceil(32)];
+
+        for (index, alive) in cells.enumerate() {
+            if alive { digits[index / 32] |= 1 << (index % 32); }
+        }
+
+        BigUint::new(digits) | ((&self.world >> area) << area)
+    }
+
+    fn active_cells(&self, changed: &BigUint) -> Vec<u16> {
+
+        let mut marked = vec![false; self.area() as usize];
+        let mut active = Vec::new();
+
+        let mut mark = |index: u16| {
+            if index < self.area() && !marked[index as usize] {
+                marked[index as usize] = true;
+                active.push(index);
+            }
+        };
+
+        for (digit, mut bits) in changed.iter_u64_digits().enumerate() {
+
+            while bits != 0 {
+
+                let index = (digit as u64 * 64 + bits.trailing_zeros() as u64) as u16;
+                bits &= bits - 1;
+
+                mark(index);
+
+                for neighbour in self.neighbours(index, self.wrap) {
+                    mark(neighbour);
+                }
+            }
+        }
+
+        active
+    }
+
+    fn margolus_step(&self, table: &[u8; 16], generation: usize) -> BigUint {
+
+        let (width, height) = (self.width as u16, self.height as u16);
+        let offset = (generation % 2) as u16;
+
+        let mut new_world = self.world.clone();
+
+        for row in (offset..height.saturating_sub(1)).step_by(2) {
+            for col in (offset..width.saturating_sub(1)).step_by(2) {
+
+                let top = row * width + col;
+                let corners = [top, top + 1, top + width, top + width + 1];
+
+                let block: u8 = corners.iter().enumerate().map(|(bit, &i)| self.get_cell(i) << bit).sum();
+                let new_block = table[block as usize];
+
+                for (bit, &i) in corners.iter().enumerate() {
+                    new_world.set_bit(i.into(), (new_block >> bit) & 1 == 1);
+                }
+            }
+        }
+
+        new_world
+    }
+
+    fn next_cell(&self, index: u16) -> bool {
+
+        let cell_count = self.count_nearby_cells(index);
+
+        if self.get_cell(index) == 1 {
+            self.rule.survival[cell_count as usize]
+        }
+        else {
+            self.rule.birth[cell_count as usize]
+        }
+    }
+
+    fn generation(&self) -> usize {
+        self.dropped + self.states.len() - 1
+    }
+
+    fn cycle_start(&self) -> Option<usize> {
+        if self.stable { self.loop_start } else { None }
+    }
+
+    fn components(&self) -> Vec<BigUint> {
+
+        let mut visited: BigUint = Zero::zero();
+        let mut components = Vec::new();
+
+        for i in 0..self.area() {
+
+            if self.get_cell(i) == 0 || visited.bit(i.into()) { continue; }
+
+            let mut component: BigUint = Zero::zero();
+            let mut pending = vec![i];
+            visited.set_bit(i.into(), true);
+
+            while let Some(index) = pending.pop() {
+
+                component.set_bit(index.into(), true);
+
+                for neighbour in self.neighbours(index, false) {
+                    if self.get_cell(neighbour) == 1 && !visited.bit(neighbour.into()) {
+                        visited.set_bit(neighbour.into(), true);
+                        pending.push(neighbour);
+                    }
+                }
+            }
+
+            components.push(component);
+        }
+
+        components
+    }
+
+    fn component_period(&self, component: &BigUint) -> Option<u64> {
+
+        let mut isolated = World::from_state(component.clone(), self.width, self.height);
+        isolated.update_mode = self.update_mode;
+        isolated.wrap = self.wrap;
+        isolated.rule = self.rule;
+
+        while !isolated.is_stable() { isolated.advance(); }
+
+        match isolated.cycle_start() {
+            Some(0) => Some(isolated.states.len() as u64),
+            _ => None
+        }
+    }
+
+    fn neighbours(&self, index: u16, wrap: bool) -> Vec<u16> {
+
+        let (width, height) = (self.width as i32, self.height as i32);
+        let row = index as i32 / width;
+        let col = index as i32 % width;
+
+        let mut neighbours = Vec::with_capacity(8);
+
+        for d_row in -1..=1 {
+            for d_col in -1..=1 {
+
+                let (mut r, mut c) = (row + d_row, col + d_col);
+
+                if wrap {
+                    r = r.rem_euclid(height);
+                    c = c.rem_euclid(width);
+                }
+
+                if (d_row, d_col) != (0, 0) && (0..height).contains(&r) && (0..width).contains(&c) {
+                    neighbours.push((r * width + c) as u16);
+                }
+            }
+        }
+
+        neighbours
+    }
+
+    fn render_grid<S: AsRef<str>, F: Fn(u16) -> S>(&self, glyph: F) -> String {
+        self.render_rows(glyph, 2, true)
+    }
+
+    fn render_rows<S: AsRef<str>, F: Fn(u16) -> S>(&self, glyph: F, cell_width: usize, border: bool) -> String {
+        
+        let mut world_str = String::from("");
+        let width = self.width as u16;
+
+        for i in 0..self.area() {
+            
+            if border && i.is_multiple_of(width) {
+                world_str.push('|');
+            }
+    
+            world_str.push_str(glyph(i).as_ref());
+
+            if (i + 1).is_multiple_of(width) {
+                if border { world_str.push('|'); }
+                world_str.push('\n');
+            }
+        }
+
+        if !border {
+            world_str.pop();
+            return world_str;
+        }
+
+        let spacer = "─".repeat(width as usize * cell_width);
+    
+        let opening_str = format!("┌{}┐\n",spacer);
+        let closing_str = format!("└{}┘",spacer);
+    
+        format!("{}{}{}",opening_str,world_str,closing_str)
+    }
+
+    fn render_cells<T: Copy>(&self, cell_px: u32, live: T, dead: T) -> (u32, u32, Vec<T>) {
+
+        let width = self.width as u32 * cell_px;
+        let height = self.height as u32 * cell_px;
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+
+        for y in 0..height {
+            for x in 0..width {
+
+                let index = (y / cell_px) as u16 * self.width as u16 + (x / cell_px) as u16;
+
+                pixels.push(if self.get_cell(index) == 1 {live} else {dead});
+            }
+        }
+
+        (width, height, pixels)
+    }
+
+    fn live_coordinates(&self) -> Vec<(u16, u16)> {
+
+        let width = self.width as u16;
+
+        (0..self.area())
+            .filter(|&i| self.get_cell(i) == 1)
+            .map(|i| (i / width, i % width))
+            .collect()
+    }
+
+    fn cell_age(&self, index: u16) -> u32 {
+        self.states.iter().rev().take_while(|state| state.bit(index.into())).count() as u32
+    }
+
+    fn index(&self, row: u8, col: u8) -> Option<u16> {
+
+        if row >= self.height || col >= self.width { return None; }
+
+        Some(row as u16 * self.width as u16 + col as u16)
+    }
+
+    fn area(&self) -> u16 {
+        self.width as u16 * self.height as u16
+    }
+
+    fn count_nearby_cells(&self, index: u16) -> u8 {
+    
+        let mut cell_count = 0;
+        let width: u16 = self.width.into();
+        let height: u16 = self.height.into();
+
+        if self.wrap {
+
+            let (row, col) = ((index / width) as i32, (index % width) as i32);
+
+            for d_row in -1..=1 {
+                for d_col in -1..=1 {
+
+                    if (d_row, d_col) == (0, 0) { continue; }
+
+                    let r = (row + d_row).rem_euclid(height as i32) as u16;
+                    let c = (col + d_col).rem_euclid(width as i32) as u16;
+
+                    cell_count += self.get_cell(r * width + c);
+                }
+            }
+
+            return cell_count;
+        }
+
+        let first_col = index.is_multiple_of(width);
+        let last_col = (index + 1).is_multiple_of(width);
+        let first_row = index < width;
+        let last_row = index >= self.area() - width;
+    
+        if !first_col { cell_count += self.get_cell(index - 1); }
+        if !last_col  { cell_count += self.get_cell(index + 1); }
+    
+        if !first_row { 
+            cell_count += self.get_cell(index - width); 
+            if !first_col { cell_count += self.get_cell(index - width - 1); }
+            if !last_col  { cell_count += self.get_cell(index - width + 1); }
+        }
+    
+        if !last_row  { 
+            cell_count += self.get_cell(index + width); 
+            if !first_col { cell_count += self.get_cell(index + width - 1); }
+            if !last_col  { cell_count += self.get_cell(index + width + 1); }
+        }
+
+        cell_count
+    }    
+
+    pub(crate) fn get_cell(&self, index: u16) -> u8 {
+        self.world.bit(index.into()) as u8
+    }   
+}
+
+impl fmt::Display for World {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.render(&RenderOptions::default()))
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct WorldState {
+    width: u8,
+    height: u8,
+    world: String,
+    states: Vec<String>,
+    initial: String,
+    max_history: Option<usize>,
+    dropped: usize,
+    stable: bool,
+    loop_start: Option<usize>,
+    update_mode: UpdateMode,
+    active_set: bool,
+    changed: Option<String>,
+    wrap: bool,
+    rule: String
+}
+
+#[cfg(feature = "serde")]
+impl From<World> for WorldState {
+
+    fn from(world: World) -> WorldState {
+
+        let hex = |state: &BigUint| state.to_str_radix(16);
+
+        WorldState {
+            width: world.width,
+            height: world.height,
+            world: hex(&world.world),
+            states: world.states.iter().map(hex).collect(),
+            initial: hex(&world.initial),
+            max_history: world.max_history,
+            dropped: world.dropped,
+            stable: world.stable,
+            loop_start: world.loop_start,
+            update_mode: world.update_mode,
+            active_set: world.active_set,
+            changed: world.changed.as_ref().map(hex),
+            wrap: world.wrap,
+            rule: world.rule.to_string()
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<WorldState> for World {
+
+    type Error = String;
+
+    fn try_from(state: WorldState) -> Result<World, Self::Error> {
+
+        let hex = |state: &String| BigUint::parse_bytes(state.as_bytes(), 16).ok_or("Board must be written as a hex number");
+
+        let mut world = World::new_rect(hex(&state.world)?, state.width, state.height).map_err(|e| e.to_string())?;
+
+        world.states = state.states.iter().map(hex).collect::<Result<_, _>>()?;
+        world.seen = HashMap::new();
+
+        for state in world.states.clone() {
+            world.remember(state);
+        }
+        world.initial = hex(&state.initial)?;
+        world.max_history = state.max_history;
+        world.dropped = state.dropped;
+        world.stable = state.stable;
+        world.loop_start = state.loop_start;
+        world.update_mode = state.update_mode;
+        world.active_set = state.active_set;
+        world.changed = state.changed.as_ref().map(hex).transpose()?;
+        world.wrap = state.wrap;
+        world.rule = Rule::parse(&state.rule).map_err(|e| e.to_string())?;
+
+        let capacity = world.area() as u64;
+        let fits = |state: &BigUint| state.bits() <= capacity;
+
+        if world.states.back() != Some(&world.world) {
+            return Err(String::from("History must end with the current generation"));
+        }
+
+        if !world.states.iter().chain(&world.changed).all(fits) || !fits(&world.initial) {
+            return Err(String::from("Recorded boards must not have cells outside of the world"));
+        }
+
+        if world.max_history.is_some_and(|max| max == 0 || world.states.len() > max) {
+            return Err(String::from("History must not exceed its maximum length"));
+        }
+
+        if world.dropped == 0 && world.states.front() != Some(&world.initial) {
+            return Err(String::from("History must start with the initial generation"));
+        }
+
+        if world.loop_start.is_some_and(|start| start >= world.states.len()) || (world.stable && world.loop_start.is_none()) {
+            return Err(String::from("Cycle must start inside of the history"));
+        }
+
+        Ok(world)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use num_bigint::BigUint;
+    use num_traits::Zero;
+    use proptest::prelude::*;
+
+    #[derive(Debug, Clone)]
+    enum Operation {
+        Advance,
+        Translate(i8, i8),
+        Rewind,
+        StepBack,
+        Reset
+    }
+
+    fn operation() -> impl Strategy<Value = Operation> {
+        prop_oneof![
+            Just(Operation::Advance),
+            Just(Operation::Rewind),
+            Just(Operation::StepBack),
+            Just(Operation::Reset),
+            (-3i8..=3, -3i8..=3).prop_map(|(d_row, d_col)| Operation::Translate(d_row, d_col))
+        ]
+    }
+
+    proptest! {
+
+        #[test]
+        fn invariants_hold_after_random_operations(
+            world_size in 1u8..=6,
+            cells in proptest::collection::vec(any::<bool>(), 36),
+            max_history in proptest::option::of(1usize..=5),
+            operations in proptest::collection::vec(operation(), 0..30)
+        ) {
+
+            let mut seed: BigUint = Zero::zero();
+            for (i, alive) in cells.iter().take((world_size as usize).pow(2)).enumerate() {
+                seed.set_bit(i as u64, *alive);
+            }
+
+            let mut world = super::WorldBuilder::new().seed(seed).size(world_size).max_history(max_history).build().unwrap();
+            world.check_invariants();
+
+            for operation in operations {
+
+                match operation {
+                    Operation::Advance => world.advance(),
+                    Operation::Translate(d_row, d_col) => world.translate(d_row, d_col),
+                    Operation::Rewind => { world.rewind_to_unique(); },
+                    Operation::StepBack => { world.step_back(); },
+                    Operation::Reset => world.reset()
+                }
+
+                world.check_invariants();
+            }
+        }
+    }
+
+    #[test]
+    fn new_world_size_greater_than_max() {
+
+        let world_size = super::World::MAX_WORLD_SIZE + 1;
+
+        let error = super::World::new(Zero::zero(),world_size).err();
+
+        assert_eq!(error, Some(super::WorldError::SizeTooLarge { max: super::World::MAX_WORLD_SIZE, got: world_size }));
+    }
+
+    #[test]
+    fn new_rejects_seeds_larger_than_the_world() {
+
+        let error = super::World::new(BigUint::from(1u16 << 9), 3).err();
+        assert_eq!(error, Some(super::WorldError::SeedTooLarge { bits: 10, capacity: 9 }));
+
+        assert!(super::World::new(BigUint::from(1u16 << 8), 3).is_ok());
+        assert!(super::WorldBuilder::new().seed(BigUint::from(1u8 << 6)).dimensions(3, 2).build().is_err());
+    }
+
+    #[test]
+    fn new_world_size_lesser_than_one() {
+
+        let world_size = 0;
+
+        let error = super::World::new(Zero::zero(),world_size).err();
+
+        assert_eq!(error, Some(super::WorldError::SizeZero));
+    }
+
+    #[test]
+    fn checkerboard_population_is_half_rounded_up() {
+
+        for world_size in 1..=7u16 {
+
+            let world = super::World::checkerboard(world_size as u8).unwrap();
+            let population: u16 = (0..world_size.pow(2)).map(|i| world.get_cell(i) as u16).sum();
+
+            assert_eq!(population, world_size.pow(2).div_ceil(2));
+        }
+    }
+
+    #[test]
+    fn checkerboard_advances_correctly() {
+
+        /*
+            101  ->  010  ->  010
+            010      101      101
+            101      010      010
+        */
+
+        let mut world = super::World::checkerboard(3).unwrap();
+
+        assert_eq!(world.world, BigUint::parse_bytes(b"101010101",2).unwrap());
+
+        world.advance();
+        assert_eq!(world.world, BigUint::parse_bytes(b"010101010",2).unwrap());
+
+        world.advance();
+        assert_eq!(world.world, BigUint::parse_bytes(b"010101010",2).unwrap());
+        assert!(world.is_stable());
+    }
+
+    #[test]
+    fn stripes_alternate_rows_by_period() {
+
+        /*
+            1111
+            1111
+            0000
+            0000
+        */
+
+        let world = super::World::stripes(4, 2).unwrap();
+
+        assert_eq!(world.world, BigUint::parse_bytes(b"0000000011111111",2).unwrap());
+        assert!(super::World::stripes(4, 0).is_err());
+    }
+
+    #[test]
+    fn builder_reserves_expected_generations() {
+
+        let mut world = super::WorldBuilder::new()
+            .seed(super::World::stripes(10, 1).unwrap().world)
+            .size(10)
+            .expected_generations(20)
+            .build()
+            .unwrap();
+
+        let capacity = world.states.capacity();
+        assert!(capacity >= 20);
+        assert!(world.seen.capacity() >= 20);
+
+        for _ in 0..10 { world.advance(); }
+
+        assert!(world.states.len() > 1);
+        assert_eq!(world.states.capacity(), capacity);
+
+        assert!(super::WorldBuilder::new().build().is_err());
+    }
+
+    #[test]
+    fn builder_matches_new_and_rejects_conflicting_sizes() {
+
+        let seed = BigUint::parse_bytes(b"011010011",2).unwrap();
+
+        let built = super::WorldBuilder::new().seed(seed.clone()).size(3).build().unwrap();
+        let world = super::World::new(seed.clone(), 3).unwrap();
+
+        assert_eq!((built.width, built.height, &built.world, &built.states), (world.width, world.height, &world.world, &world.states));
+        assert_eq!((built.wrap, built.rule, built.max_history, built.update_mode), (world.wrap, world.rule, world.max_history, world.update_mode));
+
+        let high_life = super::Rule::parse("B36/S23").unwrap();
+        let rect = super::WorldBuilder::new().dimensions(4, 2).wrapping(true).rule(high_life).build().unwrap();
+
+        assert_eq!((rect.width(), rect.height(), rect.is_wrapping(), rect.rule()), (4, 2, true, high_life));
+
+        let conflicting = super::WorldBuilder::new().seed(seed).size(3).dimensions(3, 3).build();
+        assert_eq!(conflicting.err(), Some(super::WorldError::ConflictingSizes));
+
+        let too_wide = super::WorldBuilder::new().dimensions(60, 2).build();
+        assert_eq!(too_wide.err(), Some(super::WorldError::SizeTooLarge { max: 50, got: 60 }));
+    }
+
+    #[test]
+    fn with_margin_centers_pattern() {
+
+        /*
+            010  ->  0000000
+            001      0000000
+            111      0001000
+                     0000100
+                     0011100
+                     0000000
+                     0000000
+        */
+
+        let glider = super::World::from_state(BigUint::parse_bytes(b"111100010",2).unwrap(), 3, 3);
+        let padded = glider.with_margin(2);
+
+        assert_eq!((padded.width, padded.height), (7, 7));
+        assert_eq!(padded.live_coordinates(), vec![(2, 3), (3, 4), (4, 2), (4, 3), (4, 4)]);
+        assert_eq!(padded.states, vec![padded.world.clone()]);
+
+        let clamped = super::World::from_state(Zero::zero(), 48, 48).with_margin(2);
+        assert_eq!(clamped.width, super::World::MAX_WORLD_SIZE);
+    }
+
+    #[test]
+    fn from_ascii_round_trips_through_display() {
+
+        let glider = super::World::from_ascii(".#..\n..O\n111\n", 4).unwrap();
+
+        assert_eq!(glider.to_string(), "\
+┌────────┐
+|  ██    |
+|    ██  |
+|██████  |
+|        |
+└────────┘
+");
+
+        assert_eq!(glider.world, BigUint::parse_bytes(b"0000011101000010",2).unwrap());
+
+        assert!(super::World::from_ascii("#\n#\n#\n#\n", 3).is_err());
+        assert!(super::World::from_ascii("####\n", 3).is_err());
+        assert!(super::World::from_ascii("#x#\n", 3).is_err());
+    }
+
+    #[test]
+    fn from_macrocell_parses_leaf() {
+
+        let block = super::World::from_macrocell("[M2] (golly 4.2)\n#R B3/S23\n$.**$.**$\n").unwrap();
+
+        assert_eq!((block.width, block.height), (3, 3));
+        assert_eq!(block.live_coordinates(), vec![(1, 1), (1, 2), (2, 1), (2, 2)]);
+        assert_eq!(block.rule(), super::Rule::default());
+
+        let high_life = super::World::from_macrocell("[M2] (golly 4.2)\n#R B36/S23\n**$**$\n").unwrap();
+        assert_eq!(high_life.rule().to_string(), "B36/S23");
+
+        assert!(super::World::from_macrocell("[M2]\n#R B9/S23\n**$**$\n").is_err());
+
+        assert!(super::World::from_macrocell("$.**$.**$").is_err());
+        assert!(super::World::from_macrocell("[M2]\n**$**$\n4 1 1 0 0\n").is_err());
+        assert!(super::World::from_macrocell("[M2]\n*o$\n").is_err());
+    }
+
+    #[test]
+    fn active_set_matches_full_scan() {
+
+        let fixture = BigUint::parse_bytes(b"0001001110100111",2).unwrap();
+        let glider = BigUint::parse_bytes(b"111100010",2).unwrap();
+
+        let mut glider_world = super::World::from_state(Zero::zero(), 10, 10);
+        glider_world.overlay(&super::World::from_state(glider, 3, 3), 0, 0);
+
+        for (seed, world_size) in [(fixture, 4), (glider_world.world, 10)] {
+
+            let mut full = super::World::new(seed.clone(), world_size).unwrap();
+            let mut active = super::WorldBuilder::new().seed(seed).size(world_size).active_set(true).build().unwrap();
+
+            while !full.is_stable() {
+
+                full.advance();
+                active.advance();
+
+                assert_eq!(active.world, full.world);
+                assert_eq!(active.is_stable(), full.is_stable());
+            }
+
+            assert!(full.states.len() > 3);
+            assert_eq!(active.states, full.states);
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn active_set_benchmark() {
+
+        use std::time::Instant;
+
+        // a 50x50 world full of blocks, with a single blinker in the middle
+        let mut blocks = super::World::from_fn(50, |row, col| row % 4 < 2 && col % 4 < 2).unwrap();
+        blocks.overlay(&super::World::from_state(BigUint::parse_bytes(b"0000000111000000",2).unwrap(), 4, 4), 22, 22);
+
+        let run = |active_set: bool| {
+
+            let mut world = super::WorldBuilder::new()
+                .seed(blocks.world.clone())
+                .size(50)
+                .active_set(active_set)
+                .build()
+                .unwrap();
+
+            let start = Instant::now();
+            while !world.is_stable() { world.advance(); }
+            let settle_time = start.elapsed();
+
+            // once settled, only the blinkers and their surroundings change
+            let start = Instant::now();
+            for _ in 0..10000 { std::hint::black_box(world.next_generation()); }
+
+            (settle_time, start.elapsed(), world.world)
+        };
+
+        let (full_settle, full_time, full_world) = run(false);
+        let (active_settle, active_time, active_world) = run(true);
+
+        println!("until stable: full scan {:?}, active set {:?}", full_settle, active_settle);
+        println!("10000 settled steps: full scan {:?}, active set {:?}", full_time, active_time);
+
+        assert_eq!(full_world, active_world);
+        assert!(active_time < full_time);
+    }
+
+    #[test]
+    fn rule_parse_returns_correct_value() {
+
+        let high_life = super::Rule::parse("B36/S23").unwrap();
+
+        assert_eq!(high_life.birth, [false, false, false, true, false, false, true, false, false]);
+        assert_eq!(high_life.survival, [false, false, true, true, false, false, false, false, false]);
+        assert_eq!(high_life.to_string(), "B36/S23");
+        assert_eq!(super::Rule::default().to_string(), "B3/S23");
+        assert_eq!(super::Rule::parse("B2/S").unwrap().survival, [false; 9]);
+
+        assert_eq!(super::Rule::parse("B9/S"), Err(super::WorldError::InvalidRule("Neighbour counts must be digits from 0 to 8")));
+        assert!(super::Rule::parse("X3/S2").is_err());
+        assert!(super::Rule::parse("B3S23").is_err());
+        assert!(super::Rule::parse("B3/23").is_err());
+    }
+
+    #[test]
+    fn new_with_rule_changes_births_and_deaths() {
+
+        /*
+            111
+            000
+            111
+        */
+
+        let seed = BigUint::parse_bytes(b"111000111",2).unwrap();
+
+        let conway = super::World::new(seed.clone(), 3).unwrap();
+        let high_life = super::World::new_with_rule(seed, 3, super::Rule::parse("B36/S23").unwrap()).unwrap();
+
+        assert_eq!(conway.next_cell_state(1, 1), Some(false));
+        assert_eq!(high_life.next_cell_state(1, 1), Some(true));
+        assert_eq!(high_life.with_margin(1).rule(), high_life.rule());
+    }
+
+    #[test]
+    fn is_stable_returns_correct_value() {
+
+        let mut world = super::World::from_state(Zero::zero(), 1, 1);
+
+        assert!(!world.is_stable());
+
+        world.stable = true;
+
+        assert!(world.is_stable());
+    }
+
+    #[test]
+    #[ignore]
+    fn get_cell_benchmark() {
+
+        use num_traits::One;
+        use std::time::Instant;
+
+        let world = super::World::checkerboard(50).unwrap();
+
+        // the previous implementation, building a mask with pow on every lookup
+        let masked = |index: u16| -> u8 {
+            if ((&world.world & BigUint::from(2u8).pow(index.into())) >> index) == One::one() {1} else {0}
+        };
+
+        let start = Instant::now();
+        let masked_cells: Vec<u8> = (0..2500).map(masked).collect();
+        let masked_time = start.elapsed();
+
+        let start = Instant::now();
+        let cells: Vec<u8> = (0..2500).map(|i| world.get_cell(i)).collect();
+        let bit_time = start.elapsed();
+
+        println!("pow masks for 2500 cells: {:?}, get_cell for 2500 cells: {:?}", masked_time, bit_time);
+
+        assert_eq!(cells, masked_cells);
+        assert!(bit_time < masked_time);
+    }
+
+    #[test]
+    fn get_cell_returns_correct_value() {
+        let seed = BigUint::parse_bytes(b"010010001",2).unwrap();
+        let world = super::World::from_state(seed, 3, 3);
+
+        assert_eq!(world.get_cell(0),1);
+        assert_eq!(world.get_cell(1),0);
+        assert_eq!(world.get_cell(2),0);
+        assert_eq!(world.get_cell(3),0);
+        assert_eq!(world.get_cell(4),1);
+        assert_eq!(world.get_cell(5),0);
+        assert_eq!(world.get_cell(6),0);
+        assert_eq!(world.get_cell(7),1);
+        assert_eq!(world.get_cell(8),0);
+    }
+
+    #[test]
+    fn async_advance_diverges_from_sync() {
+
+        /*
+            010  ->  000  (sync)
+            010      111
+            010      000
+
+            010  ->  000  (async)
+            010      000
+            010      000
+        */
+
+        let seed = BigUint::parse_bytes(b"010010010",2).unwrap();
+
+        let mut sync = super::World::from_state(seed.clone(), 3, 3);
+        sync.advance();
+        assert_eq!(sync.world, BigUint::parse_bytes(b"000111000",2).unwrap());
+
+        for order in [super::AsyncOrder::Sequential, super::AsyncOrder::Reverse] {
+
+            let mut world = super::World::from_state(seed.clone(), 3, 3);
+            world.set_update_mode(super::UpdateMode::Async { order });
+            world.advance();
+
+            assert_eq!(world.world, Zero::zero());
+        }
+
+        let mode = super::UpdateMode::Async { order: super::AsyncOrder::Random(42) };
+
+        let mut first = super::World::checkerboard(6).unwrap();
+        let mut second = super::World::checkerboard(6).unwrap();
+        first.set_update_mode(mode);
+        second.set_update_mode(mode);
+
+        for _ in 0..5 {
+            first.advance();
+            second.advance();
+            assert_eq!(first.world, second.world);
+        }
+    }
+
+    #[test]
+    fn advance_reverse_undoes_margolus_steps() {
+
+        // rotates every block clockwise: nw -> ne -> se -> sw -> nw
+        let mut table = [0u8; 16];
+        for (block, next) in table.iter_mut().enumerate() {
+            let block = block as u8;
+            *next = ((block & 1) << 1) | ((block & 2) << 2) | ((block & 8) >> 1) | ((block & 4) >> 2);
+        }
+
+        let seed = BigUint::parse_bytes(b"100110010011101001110010100110001101",2).unwrap();
+        let mut world = super::World::from_state(seed.clone(), 6, 6);
+        world.set_update_mode(super::UpdateMode::Margolus { table });
+
+        let mut snapshots = vec![seed];
+
+        for _ in 0..4 {
+            world.advance();
+            snapshots.push(world.world.clone());
+        }
+
+        assert_eq!(world.states.len(), 5);
+
+        for state in world.states.iter_mut() {
+            *state = Zero::zero();
+        }
+
+        for snapshot in snapshots.iter().rev().skip(1) {
+            world.advance_reverse().unwrap();
+            assert_eq!(&world.world, snapshot);
+        }
+
+        assert_eq!(world.advance_reverse(), Err(super::WorldError::AtFirstGeneration));
+
+        world.set_update_mode(super::UpdateMode::Margolus { table: [0; 16] });
+        assert_eq!(world.advance_reverse(), Err(super::WorldError::NotReversible));
+
+        world.set_update_mode(super::UpdateMode::Sync);
+        assert_eq!(world.advance_reverse(), Err(super::WorldError::NotReversible));
+    }
+
+    #[test]
+    fn smooth_grows_regions_of_noise() {
+
+        use rand::{ Rng, SeedableRng, rngs::StdRng };
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut seed: BigUint = Zero::zero();
+
+        for i in 0..30u64.pow(2) {
+            seed.set_bit(i, rng.gen_bool(0.45));
+        }
+
+        let mut world = super::World::from_state(seed, 30, 30);
+
+        let largest_cluster = |world: &super::World| {
+            world.components().iter().map(|component| component.count_ones()).max().unwrap_or(0)
+        };
+
+        let noise = largest_cluster(&world);
+
+        for _ in 0..4 { world.smooth(4, 3); }
+
+        assert!(world.history_len() > 1);
+        assert!(largest_cluster(&world) > noise);
+    }
+
+    #[test]
+    fn max_history_bounds_memory_and_detects_short_cycles() {
+
+        let glider = BigUint::parse_bytes(b"111100010",2).unwrap();
+
+        let mut bounded = super::WorldBuilder::new().size(10).max_history(Some(4)).build().unwrap();
+        bounded.wrap = true;
+        bounded.overlay(&super::World::from_state(glider.clone(), 3, 3), 0, 0);
+
+        let mut unbounded = bounded.clone();
+        unbounded.max_history = None;
+
+        for _ in 0..200 {
+            bounded.advance();
+            unbounded.advance();
+        }
+
+        // the glider loops every 40 generations, longer than the window
+        assert!(!bounded.is_stable());
+        assert!(unbounded.is_stable());
+        assert_eq!(bounded.history_len(), 4);
+        assert!(bounded.estimated_memory_bytes() < unbounded.estimated_memory_bytes() / 4);
+
+        // R-pentomino, which settles into still lifes after 47 generations in a 12 x 12 world
+        let r_pentomino = |max_history| {
+
+            let mut world = super::WorldBuilder::new().size(12).max_history(max_history).build().unwrap();
+            world.overlay(&super::World::from_state(BigUint::parse_bytes(b"010011110",2).unwrap(), 3, 3), 5, 5);
+
+            while !world.is_stable() { world.advance(); }
+
+            world
+        };
+
+        let bounded = r_pentomino(Some(4));
+
+        assert_eq!(bounded.history_len(), 4);
+        assert_eq!(bounded.cycle_info(), r_pentomino(None).cycle_info());
+        assert_eq!(bounded.period(), Some(1));
+
+        assert!(super::WorldBuilder::new().size(3).max_history(Some(0)).build().is_err());
+    }
+
+    #[test]
+    fn reset_restores_the_first_generation() {
+
+        let seed = BigUint::parse_bytes(b"011010011",2).unwrap();
+        let fresh = super::World::new(seed.clone(), 3).unwrap();
+
+        for max_history in [None, Some(2)] {
+
+            let mut world = super::WorldBuilder::new().seed(seed.clone()).size(3).max_history(max_history).build().unwrap();
+
+            while !world.is_stable() { world.advance(); }
+            let cycle_info = world.cycle_info();
+
+            world.reset();
+
+            assert_eq!((&world.world, &world.states, world.stable), (&fresh.world, &fresh.states, fresh.stable));
+            assert_eq!(world.cycle_info(), None);
+
+            // replaying the run ends the same way
+            while !world.is_stable() { world.advance(); }
+            assert_eq!(world.cycle_info(), cycle_info);
+        }
+    }
+
+    #[test]
+    fn step_back_restores_previous_generations() {
+
+        /*
+            0100
+            0010
+            1110
+            0000
+        */
+
+        let seed = BigUint::parse_bytes(b"0000011101000010",2).unwrap();
+        let mut world = super::World::from_state(seed, 4, 4);
+
+        assert!(!world.step_back());
+
+        for _ in 0..3 { world.advance(); }
+
+        let generations = world.states.clone();
+
+        assert!(world.step_back());
+        assert!(world.step_back());
+
+        assert_eq!(world.world, generations[1]);
+        assert_eq!(world.history_len(), 2);
+
+        world.advance();
+        world.advance();
+
+        assert_eq!(world.states, generations);
+
+        let mut blinker = super::World::from_state(BigUint::parse_bytes(b"010010010",2).unwrap(), 3, 3);
+
+        while !blinker.is_stable() { blinker.advance(); }
+
+        assert!(blinker.step_back());
+        assert!(!blinker.is_stable());
+        assert_eq!(blinker.world, BigUint::parse_bytes(b"010010010",2).unwrap());
+    }
+
+    #[test]
+    fn rewind_to_unique_skips_repeated_states() {
+
+        let vertical = BigUint::parse_bytes(b"010010010",2).unwrap();
+        let horizontal = BigUint::parse_bytes(b"000111000",2).unwrap();
+
+        let mut blinker = super::World::from_state(vertical.clone(), 3, 3);
+
+        while !blinker.is_stable() { blinker.advance(); }
+
+        assert!(blinker.rewind_to_unique());
+        assert_eq!(blinker.world, vertical);
+        assert_eq!(blinker.states, vec![vertical.clone()]);
+        assert!(!blinker.is_stable());
+
+        assert!(!blinker.rewind_to_unique());
+        assert_eq!(blinker.world, vertical);
+
+        let mut world = super::World::from_state(vertical.clone(), 3, 3);
+        world.states = vec![vertical.clone(), horizontal.clone(), horizontal.clone()].into();
+        world.seen = std::collections::HashMap::from([(vertical.clone(), 1), (horizontal.clone(), 2)]);
+        world.world = horizontal;
+
+        assert!(world.rewind_to_unique());
+        assert_eq!(world.world, vertical);
+        assert_eq!(world.states.len(), 1);
+        world.check_invariants();
+    }
+
+    #[test]
+    fn translate_moves_cells() {
+
+        /*
+            1100  ->  0000
+            1100      0110
+            0000      0110
+            0000      0000
+        */
+
+        let seed = BigUint::parse_bytes(b"0000000000110011",2).unwrap();
+        let mut world = super::World::from_state(seed, 4, 4);
+        world.stable = true;
+
+        world.translate(1, 1);
+
+        let expected = BigUint::parse_bytes(b"0000011001100000",2).unwrap();
+
+        assert_eq!(world.world, expected);
+        assert_eq!(world.states, vec![expected]);
+        assert!(!world.is_stable());
+    }
+
+    #[test]
+    fn translate_drops_cells_past_the_edge() {
+
+        /*
+            0000  ->  0000
+            0110      0000
+            0110      0000
+            0000      1100
+        */
+
+        let seed = BigUint::parse_bytes(b"0000011001100000",2).unwrap();
+        let mut world = super::World::from_state(seed, 4, 4);
+
+        world.translate(2, -1);
+        assert_eq!(world.world, BigUint::parse_bytes(b"0011000000000000",2).unwrap());
+
+        world.translate(-4, 0);
+        assert_eq!(world.world, Zero::zero());
+    }
+
+    #[test]
+    fn is_agar_returns_correct_value() {
+
+        // horizontal stripes repeat every other row
+        let stripes = super::World::stripes(4, 1).unwrap();
+        assert_eq!(stripes.is_agar(), Some((2, 1)));
+
+        let wide_stripes = super::World::stripes(8, 2).unwrap();
+        assert_eq!(wide_stripes.is_agar(), Some((4, 1)));
+
+        let checkerboard = super::World::checkerboard(4).unwrap();
+        assert_eq!(checkerboard.is_agar(), Some((2, 2)));
+
+        // only periodic along its rows, as 5 rows cannot alternate evenly
+        let uneven = super::World::stripes(5, 1).unwrap();
+        assert_eq!(uneven.is_agar(), Some((5, 1)));
+
+        let seed = BigUint::parse_bytes(b"010010010",2).unwrap();
+        assert_eq!(super::World::from_state(seed, 3, 3).is_agar(), Some((1, 3)));
+
+        let seed = BigUint::parse_bytes(b"011010001",2).unwrap();
+        assert_eq!(super::World::from_state(seed, 3, 3).is_agar(), None);
+
+        assert_eq!(super::World::from_state(Zero::zero(), 3, 3).is_agar(), None);
+    }
+
+    #[test]
+    fn is_agar_reports_periods_of_diagonal_stripes() {
+
+        /*
+            100100
+            010010
+            001001
+            100100
+            ...
+        */
+
+        let diagonal = super::World::from_fn(6, |row, col| (row + 6 - col) % 3 == 0).unwrap();
+        assert_eq!(diagonal.is_agar(), Some((3, 3)));
+    }
+
+    #[test]
+    fn next_cell_state_predicts_advance() {
+
+        let seed = BigUint::parse_bytes(b"0000000100001000010000000",2).unwrap();
+        let mut world = super::World::from_state(seed, 5, 5);
+
+        let predicted: Vec<Option<bool>> = (0..25).map(|i| world.next_cell_state(i / 5, i % 5)).collect();
+
+        world.advance();
+
+        for (i, prediction) in predicted.iter().enumerate() {
+            assert_eq!(*prediction, Some(world.get_cell(i as u16) == 1));
+        }
+
+        assert_eq!(predicted.iter().filter(|p| **p == Some(true)).count(), 3);
+        assert_eq!(world.next_cell_state(5, 0), None);
+        assert_eq!(world.next_cell_state(0, 5), None);
+    }
+
+    #[test]
+    fn history_len_and_memory_grow_with_generations() {
+
+        let mut world = super::World::stripes(10, 1).unwrap();
+        let mut memory = world.estimated_memory_bytes();
+
+        assert_eq!(world.history_len(), 1);
+
+        for generation in 1..=3 {
+
+            world.advance();
+
+            assert_eq!(world.history_len(), generation + 1);
+            assert!(world.estimated_memory_bytes() > memory);
+
+            memory = world.estimated_memory_bytes();
+        }
+
+        // the seen states hold a second copy of the history
+        let digits_bytes = |state: &BigUint| state.bits().div_ceil(64) as usize * 8;
+        let history_bytes = std::mem::size_of::<super::World>()
+            + digits_bytes(&world.world)
+            + world.states.capacity() * std::mem::size_of::<BigUint>()
+            + world.states.iter().map(digits_bytes).sum::<usize>();
+
+        assert!(memory >= history_bytes
+            + world.seen.len() * std::mem::size_of::<(BigUint, usize)>()
+            + world.seen.keys().map(digits_bytes).sum::<usize>());
+    }
+
+    #[test]
+    fn trajectory_hash_matches_identical_runs() {
+
+        let run = |seed: &[u8], generations: usize| {
+
+            let mut world = super::World::new(BigUint::parse_bytes(seed, 2).unwrap(), 4).unwrap();
+            for _ in 0..generations { world.advance(); }
+
+            world.trajectory_hash()
+        };
+
+        assert_eq!(run(b"0001001110100111", 3), run(b"0001001110100111", 3));
+        assert_ne!(run(b"0001001110100111", 3), run(b"0001001110100110", 3));
+        assert_ne!(run(b"0001001110100111", 3), run(b"0001001110100111", 2));
+
+        // pinned so that golden values kept elsewhere stay valid
+        assert_eq!(run(b"0001001110100111", 3), 0xa873a2a8312af5d1);
+    }
+
+    #[test]
+    fn oldest_cell_returns_correct_value() {
+
+        assert_eq!(super::World::from_state(Zero::zero(), 3, 3).oldest_cell(), None);
+
+        let seed = BigUint::parse_bytes(b"0000000000000000000000000000000001000000000100000000010000000000000000000000000000000000110000000011",2).unwrap();
+        let mut world = super::World::from_state(seed, 10, 10);
+
+        for _ in 0..3 { world.advance(); }
+
+        let block_age = world.states.len() as u32;
+
+        assert_eq!(world.oldest_cell(), Some((0, 0, block_age)));
+
+        for index in [0, 1, 10, 11] {
+            assert_eq!(world.cell_age(index), block_age);
+        }
+
+        for index in [55, 57] {
+            assert!(world.cell_age(index) < block_age);
+        }
+    }
+
+    #[test]
+    fn temperature_returns_correct_value() {
+
+        let seed = BigUint::parse_bytes(b"0000011001100000",2).unwrap();
+        let mut block = super::World::from_state(seed, 4, 4);
+
+        block.advance();
+        assert_eq!(block.temperature(), 0.0);
+
+        let seed = BigUint::parse_bytes(b"010010010",2).unwrap();
+        let mut blinker = super::World::from_state(seed, 3, 3);
+
+        blinker.advance();
+
+        assert_eq!(blinker.changed_cells(), vec![(0, 1), (1, 0), (1, 2), (2, 1)]);
+        assert_eq!(blinker.temperature(), 4.0 / 9.0);
+    }
+
+    #[test]
+    fn cell_last_changed_returns_correct_value() {
+
+        /*
+            010  ->  000
+            010      111
+            010      000
+        */
+
+        let seed = BigUint::parse_bytes(b"010010010",2).unwrap();
+        let mut world = super::World::from_state(seed, 3, 3);
+
+        assert_eq!(world.cell_last_changed(0, 1), None);
+
+        world.advance();
+        world.advance();
+
+        assert_eq!(world.cell_last_changed(1, 1), None);
+        assert_eq!(world.cell_last_changed(0, 1), Some(1));
+        assert_eq!(world.cell_last_changed(1, 0), Some(1));
+        assert_eq!(world.cell_last_changed(0, 0), None);
+        assert_eq!(world.cell_last_changed(3, 0), None);
+    }
+
+    #[test]
+    fn spacetime_pgm_stacks_generations() {
+
+        let seed = BigUint::parse_bytes(b"010010010",2).unwrap();
+        let mut world = super::World::from_state(seed, 3, 3);
+
+        world.advance();
+        world.advance();
+
+        let header = b"P5\n3 2\n255\n";
+        let image = world.spacetime_pgm(1, 1);
+
+        assert_eq!(&image[..header.len()], header);
+        assert_eq!(&image[header.len()..], &[255, 0, 255, 0, 0, 0]);
+
+        let header = b"P5\n6 4\n255\n";
+        let image = world.spacetime_pgm(1, 2);
+
+        assert_eq!(&image[..header.len()], header);
+        assert_eq!(image.len(), header.len() + 6 * 4);
+    }
+
+    #[test]
+    fn cycle_info_returns_correct_value() {
+
+        let seed = BigUint::parse_bytes(b"0000000100001000010000000",2).unwrap();
+        let mut blinker = super::World::from_state(seed, 5, 5);
+
+        blinker.advance();
+        assert_eq!(blinker.cycle_info(), None);
+
+        blinker.advance();
+        assert_eq!(blinker.cycle_info(), Some((0, 2)));
+
+        let seed = BigUint::parse_bytes(b"011010011",2).unwrap();
+        let mut world = super::World::from_state(seed, 3, 3);
+
+        while !world.is_stable() { world.advance(); }
+
+        assert_eq!(world.cycle_info(), Some((4, 5)));
+    }
+
+    #[test]
+    fn stability_tells_still_lifes_from_oscillators() {
+
+        use super::Stability;
+
+        let mut block = super::World::new(BigUint::parse_bytes(b"0000011001100000",2).unwrap(), 4).unwrap();
+        let mut blinker = super::World::new(BigUint::parse_bytes(b"0000000100001000010000000",2).unwrap(), 5).unwrap();
+
+        /*
+            01000
+            00100
+            11100
+            00000
+            00000
+        */
+
+        let mut glider = super::World::new(BigUint::parse_bytes(b"0000000000001110010000010",2).unwrap(), 5).unwrap();
+
+        for _ in 0..3 {
+            block.advance();
+            blinker.advance();
+            glider.advance();
+        }
+
+        assert_eq!(block.stability(), Stability::StillLife);
+        assert_eq!(blinker.stability(), Stability::Oscillating { period: 2 });
+        assert_eq!(glider.stability(), Stability::Active);
+    }
+
+    #[test]
+    fn period_returns_correct_value() {
+
+        let mut blinker = super::World::from_state(BigUint::parse_bytes(b"010010010",2).unwrap(), 3, 3);
+
+        assert_eq!(blinker.period(), None);
+
+        while !blinker.is_stable() { blinker.advance(); }
+
+        assert_eq!(blinker.period(), Some(2));
+
+        let mut block = super::World::from_state(BigUint::parse_bytes(b"0000011001100000",2).unwrap(), 4, 4);
+
+        while !block.is_stable() { block.advance(); }
+
+        assert_eq!(block.period(), Some(1));
+    }
+
+    #[test]
+    fn collect_matching_returns_filtered_generations() {
+
+        let vertical = BigUint::parse_bytes(b"010010010",2).unwrap();
+        let horizontal = BigUint::parse_bytes(b"000111000",2).unwrap();
+
+        let mut blinker = super::World::from_state(vertical.clone(), 3, 3);
+        let phases: Vec<(u64, BigUint)> = blinker.collect_matching(10, |_| true)
+            .into_iter()
+            .map(|(generation, world)| (generation, world.world))
+            .collect();
+
+        assert_eq!(phases, vec![(0, vertical.clone()), (1, horizontal)]);
+        assert!(blinker.is_stable());
+
+        let mut blinker = super::World::from_state(vertical.clone(), 3, 3);
+        let matching = blinker.collect_matching(0, |_| true);
+
+        assert_eq!(matching.len(), 1);
+        assert_eq!(blinker.states.len(), 1);
+
+        let mut world = super::World::from_state(BigUint::parse_bytes(b"011010011",2).unwrap(), 3, 3);
+        let generations: Vec<u64> = world.collect_matching(10, |world| world.world.count_ones() == 3)
+            .into_iter()
+            .map(|(generation, _)| generation)
+            .collect();
+
+        assert_eq!(generations, vec![2]);
+    }
+
+    #[test]
+    fn is_phoenix_returns_correct_value() {
+
+        /*
+            ...O....
+            ...O.O..
+            .O......
+            ......OO
+            OO......
+            ......O.
+            ..O.O...
+            ....O...
+        */
+
+        let seed = BigUint::parse_bytes(b"0001000000010100010000000000001111000000000000100010100000001000",2).unwrap();
+        let mut phoenix = super::World::from_state(seed, 8, 8);
+
+        assert!(!phoenix.is_phoenix());
+
+        while !phoenix.is_stable() { phoenix.advance(); }
+
+        assert_eq!(phoenix.states.len(), 2);
+        assert!(phoenix.is_phoenix());
+
+        let seed = BigUint::parse_bytes(b"010010010",2).unwrap();
+        let mut blinker = super::World::from_state(seed, 3, 3);
+
+        while !blinker.is_stable() { blinker.advance(); }
+
+        assert!(!blinker.is_phoenix());
+    }
+
+    #[test]
+    fn is_methuselah_returns_correct_value() {
+
+        // R-pentomino, which settles after 100 generations in a 20 x 20 world
+        let r_pentomino = super::World::from_fn(20, |row, col| {
+            matches!((row, col), (9, 10) | (9, 11) | (10, 9) | (10, 10) | (11, 10))
+        }).unwrap();
+
+        assert!(r_pentomino.is_methuselah(50, 200));
+        assert!(!r_pentomino.is_methuselah(50, 60));
+        assert!(!r_pentomino.is_methuselah(150, 200));
+        assert_eq!(r_pentomino.states.len(), 1);
+        assert!(!r_pentomino.is_stable());
+
+        let block = super::World::from_fn(20, |row, col| (9..11).contains(&row) && (9..11).contains(&col)).unwrap();
+
+        assert!(!block.is_methuselah(50, 200));
+        assert!(block.is_methuselah(0, 200));
+    }
+
+    #[test]
+    fn long_runs_detect_cycles_at_the_same_generation() {
+
+        use rand::{ SeedableRng, rngs::StdRng };
+
+        let r_pentomino = super::World::from_fn(20, |row, col| {
+            matches!((row, col), (9, 10) | (9, 11) | (10, 9) | (10, 10) | (11, 10))
+        }).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(283);
+        let mut worlds = vec![r_pentomino.clone()];
+
+        for wrap in [false, true] {
+            let mut world = super::World::random(20, 0.4, &mut rng).unwrap();
+            world.wrap = wrap;
+            worlds.push(world);
+        }
+
+        for mut world in worlds {
+
+            // reference run, scanning the whole history for every new state
+            let mut states = vec![world.world.clone()];
+
+            let expected = loop {
+
+                let mut last = super::World::from_state(states[states.len() - 1].clone(), 20, 20);
+                last.wrap = world.wrap;
+                let next = last.sync_step();
+
+                match states.iter().position(|state| *state == next) {
+                    Some(start) => break (start as u64, states.len() as u64),
+                    None => states.push(next)
+                }
+            };
+
+            while !world.is_stable() { world.advance(); }
+
+            assert_eq!(world.cycle_info(), Some(expected));
+        }
+
+        // the R-pentomino settles into still lifes and blinkers
+        let mut r_pentomino = r_pentomino;
+        while !r_pentomino.is_stable() { r_pentomino.advance(); }
+
+        assert_eq!(r_pentomino.cycle_info(), Some((100, 101)));
+    }
+
+    #[test]
+    fn still_life_fraction_returns_correct_value() {
+
+        /*
+            11000000
+            11000000
+            00000000
+            00000000
+            00000100
+            00000100
+            00000100
+            00000000
+        */
+
+        let seed = BigUint::parse_bytes(b"0000000000100000001000000010000000000000000000000000001100000011",2).unwrap();
+        let mut world = super::World::from_state(seed, 8, 8);
+
+        assert_eq!(world.still_life_fraction(), None);
+
+        while !world.is_stable() { world.advance(); }
+
+        assert_eq!(world.still_life_fraction(), Some(4.0 / 7.0));
+    }
+
+    #[test]
+    fn period_census_returns_correct_value() {
+
+        /*
+            1100010000
+            1100010000
+            0000010000
+            0000000000
+            0000000000
+            0000000000
+            0000000000
+            0000011100
+            0000000000
+            0000000000
+        */
+
+        let seed = BigUint::parse_bytes(b"0000000000000000000000111000000000000000000000000000000000000000000000000010000000001000110000100011",2).unwrap();
+        let mut world = super::World::from_state(seed, 10, 10);
+
+        assert_eq!(world.period_census(), None);
+
+        while !world.is_stable() { world.advance(); }
+
+        let census = world.period_census().unwrap();
+
+        assert_eq!(census.len(), 2);
+        assert_eq!(census[&1], 1);
+        assert_eq!(census[&2], 2);
+    }
+
+    #[test]
+    fn export_components_crops_objects() {
+
+        /*
+            11000
+            11000
+            00000
+            00011
+            00011
+        */
+
+        let seed = BigUint::parse_bytes(b"1100011000000000001100011",2).unwrap();
+        let world = super::World::from_state(seed, 5, 5);
+
+        let block = String::from("x = 2, y = 2, rule = B3/S23\n2o$2o!\n");
+
+        assert_eq!(world.export_components(), vec![
+            (super::BoundingBox { row: 0, col: 0, height: 2, width: 2 }, block.clone()),
+            (super::BoundingBox { row: 3, col: 3, height: 2, width: 2 }, block)
+        ]);
+    }
+
+    #[test]
+    fn canonical_symmetric_ignores_orientation() {
+
+        /*
+            010      100
+            001  ,   101
+            111      110
+        */
+
+        let glider = BigUint::parse_bytes(b"111100010",2).unwrap();
+        let glider = super::World::from_state(glider, 3, 3);
+
+        let rotated = BigUint::parse_bytes(b"011101001",2).unwrap();
+        let rotated = super::World::from_state(rotated, 3, 3);
+
+        let mut world = super::World::new(Zero::zero(), 5).unwrap();
+        world.overlay(&glider, 0, 1);
+
+        let mut other = super::World::new(Zero::zero(), 6).unwrap();
+        other.overlay(&rotated, 2, 3);
+
+        let canonical = world.canonical_symmetric();
+
+        assert_eq!(canonical, other.canonical_symmetric());
+        assert_eq!(canonical.0, 3);
+        assert_eq!(canonical.1, 3);
+        assert_eq!(canonical.2.count_ones(), 5);
+
+        assert_eq!(super::World::new(Zero::zero(), 3).unwrap().canonical_symmetric(), (0, 0, Zero::zero()));
+    }
+
+    #[test]
+    fn preview_overlay_shows_ghost_cells() {
+
+        let seed = BigUint::parse_bytes(b"0001000000000000",2).unwrap();
+        let world = super::World::from_state(seed.clone(), 4, 4);
+
+        let block = super::World::from_state(BigUint::parse_bytes(b"1111",2).unwrap(), 2, 2);
+
+        assert_eq!(world.preview_overlay(&block, 2, 2), "\
+┌────────┐
+|        |
+|        |
+|    ░░░░|
+|██  ░░░░|
+└────────┘
+");
+
+        assert_eq!(world.preview_overlay(&block, 3, 0), "\
+┌────────┐
+|        |
+|        |
+|        |
+|██░░    |
+└────────┘
+");
+
+        assert_eq!(world.world, seed);
+        assert_eq!(world.states, vec![seed]);
+    }
+
+    #[test]
+    fn render_rgba_paints_cells() {
+
+        let live = [0, 0, 0, 255];
+        let dead = [255, 255, 255, 255];
+
+        let seed = BigUint::parse_bytes(b"010010010",2).unwrap();
+        let world = super::World::from_state(seed, 3, 3);
+
+        let (width, height, buffer) = world.render_rgba(2, live, dead);
+
+        assert_eq!((width, height), (6, 6));
+        assert_eq!(buffer.len(), (width * height * 4) as usize);
+
+        let pixel = |x: u32, y: u32| &buffer[((y * width + x) * 4) as usize..][..4];
+
+        assert_eq!(pixel(2, 0), &live);
+        assert_eq!(pixel(3, 5), &live);
+        assert_eq!(pixel(1, 1), &dead);
+        assert_eq!(pixel(4, 2), &dead);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn save_png_writes_black_cells_on_white() {
+
+        /*
+            010
+            011
+            100
+        */
+
+        let world = super::World::from_state(BigUint::parse_bytes(b"001110010",2).unwrap(), 3, 3);
+        let path = std::env::temp_dir().join(format!("rust_game_of_life_{}.png", std::process::id()));
+
+        world.save_png(&path, 1).unwrap();
+
+        let image = image::open(&path).unwrap().to_luma8();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(image.dimensions(), (3, 3));
+        assert_eq!(image.into_raw(), vec![255, 0, 255, 255, 0, 0, 0, 255, 255]);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn record_gif_stops_once_stable_or_at_max_frames() {
+
+        use image::{ AnimationDecoder, codecs::gif::GifDecoder };
+
+        let frames = |path: &std::path::Path| {
+            let decoder = GifDecoder::new(std::io::BufReader::new(std::fs::File::open(path).unwrap())).unwrap();
+            decoder.into_frames().collect::<Result<Vec<_>, _>>().unwrap()
+        };
+
+        let path = std::env::temp_dir().join(format!("rust_game_of_life_{}.gif", std::process::id()));
+
+        let blinker = super::World::new(BigUint::parse_bytes(b"0000000100001000010000000",2).unwrap(), 5).unwrap();
+        super::World::record_gif(blinker.clone(), &path, 2, 100, 10).unwrap();
+
+        let recorded = frames(&path);
+
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[0].buffer().dimensions(), (10, 10));
+        assert_eq!(recorded[0].delay().numer_denom_ms(), (100, 1));
+
+        super::World::record_gif(blinker, &path, 1, 100, 1).unwrap();
+        assert_eq!(frames(&path).len(), 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn render_indexed_references_palette() {
+
+        let seed = BigUint::parse_bytes(b"010010010",2).unwrap();
+        let world = super::World::from_state(seed, 3, 3);
+
+        let (width, height, indices, palette) = world.render_indexed(3);
+
+        assert_eq!((width, height), (9, 9));
+        assert_eq!(indices.len(), (width * height) as usize);
+        assert!(indices.iter().all(|&index| (index as usize) < palette.len()));
+
+        assert_eq!(palette[indices[4] as usize], [0, 0, 0]);
+        assert_eq!(palette[indices[0] as usize], [255, 255, 255]);
+    }
+
+    #[test]
+    fn to_html_contains_every_generation() {
+
+        let seed = BigUint::parse_bytes(b"011010011",2).unwrap();
+        let mut world = super::World::from_state(seed, 3, 3);
+
+        while !world.is_stable() { world.advance(); }
+
+        let html = world.to_html(250);
+
+        let frames: Vec<&str> = html.lines()
+            .skip_while(|line| *line != "const frames = [")
+            .skip(1)
+            .take_while(|line| *line != "];")
+            .collect();
+
+        assert_eq!(frames.len(), world.states.len());
+        assert_eq!(frames[0], "    \"110010110\",");
+        assert!(html.contains("}, 250);"));
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+
+        let script_start = html.find("<script>").unwrap() + "<script>".len();
+        let script_end = html.find("</script>").unwrap();
+        let markup = format!("{}{}", &html[..script_start], &html[script_end..]);
+
+        let mut open_tags = Vec::new();
+
+        for tag in markup.split('<').skip(1).map(|t| t.split(['>', ' ']).next().unwrap()) {
+            match tag {
+                "!DOCTYPE" | "meta" => (),
+                closing if closing.starts_with('/') => assert_eq!(open_tags.pop(), Some(&closing[1..])),
+                opening => open_tags.push(opening)
+            }
+        }
+
+        assert!(open_tags.is_empty());
+    }
+
+    #[test]
+    fn ensemble_stats_returns_correct_value() {
+
+        let histories = vec![vec![2, 4, 6], vec![4, 8], vec![]];
+
+        assert_eq!(super::World::ensemble_stats(&histories), vec![(3.0, 1.0), (6.0, 2.0), (7.0, 1.0)]);
+        assert!(super::World::ensemble_stats(&[]).is_empty());
+    }
+
+    #[test]
+    fn overlay_copies_cells_at_position() {
+
+        /*
+            0000      11      0000
+            0000  +   11  ->  0000
+            0000              0011
+            1000              1011
+        */
+
+        let seed = BigUint::parse_bytes(b"0001000000000000",2).unwrap();
+        let mut world = super::World::from_state(seed, 4, 4);
+        world.stable = true;
+
+        let block = BigUint::parse_bytes(b"1111",2).unwrap();
+        let block = super::World::from_state(block, 2, 2);
+
+        world.overlay(&block, 2, 2);
+
+        assert_eq!(world.world, BigUint::parse_bytes(b"1101110000000000",2).unwrap());
+        assert!(!world.is_stable());
+
+        world.overlay(&block, 3, 3);
+
+        assert_eq!(world.world, BigUint::parse_bytes(b"1101110000000000",2).unwrap());
+    }
+
+    #[test]
+    fn population_and_is_extinct_return_correct_value() {
+
+        /*
+            010
+            001
+            111
+            1   <- outside of the grid
+        */
 
-    fn get_cell(&self, index: u16) -> u8 {
-        if ((&self.world & BigUint::from(2u8).pow(index.into())) >> index) == One::one() {1} else {0}
-    }   
-}
+        let seed = BigUint::parse_bytes(b"1111100010",2).unwrap();
+        let mut glider = super::World::from_state(seed, 3, 3);
 
-impl fmt::Display for World {
+        assert_eq!(glider.population(), 5);
 
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        
-        let mut world_str = String::from("");
-        let world_size = self.world_size as u16;
+        let populations: Vec<u64> = (0..3).map(|_| { glider.advance(); glider.population() }).collect();
 
-        let spacer = "─".repeat(world_size as usize * 2);
-    
-        let opening_str = format!("┌{}┐\n",spacer);
-        let closing_str = format!("└{}┘",spacer);
-    
-        for i in 0..world_size.pow(2) {
-            
-            if i % world_size == 0 {
-                world_str.push('|');
-            }
-    
-            let current = self.get_cell(i);        
-    
-            world_str.push_str(if current == 1 {"██"} else {"  "});
+        assert_eq!(populations, vec![4, 3, 4]);
+        assert!(!glider.is_extinct());
 
-            if (i + 1) % world_size == 0 {
-                world_str.push('|');
-                world_str.push('\n');
-            }
-        }
-    
-        writeln!(f,"{}{}{}",opening_str,world_str,closing_str)  
+        let mut pair = super::World::from_state(BigUint::parse_bytes(b"000000011",2).unwrap(), 3, 3);
+
+        pair.advance();
+
+        assert!(pair.is_extinct());
     }
-}
 
-#[cfg(test)]
-mod tests {
+    #[test]
+    fn cell_and_live_cells_return_correct_value() {
 
-    use num_bigint::BigUint;
-    use num_traits::Zero;
+        /*
+            010
+            001
+            111
+            1   <- outside of the grid
+        */
+
+        let seed = BigUint::parse_bytes(b"1111100010",2).unwrap();
+        let world = super::World::from_state(seed, 3, 3);
+
+        assert!(world.cell(1, 0));
+        assert!(!world.cell(0, 0));
+        assert!(world.cell(2, 1));
+        assert!(world.cell(0, 2));
+
+        assert_eq!(world.live_cells().collect::<Vec<_>>(), vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    }
 
     #[test]
-    #[should_panic]
-    fn new_world_size_greater_than_max() {
+    fn set_cell_and_toggle_cell_update_the_world() {
 
-        let world_size = super::World::MAX_WORLD_SIZE + 1;
+        let mut world = super::World::new(Zero::zero(), 3).unwrap();
+
+        world.set_cell(1, 1, true);
+        assert!(world.cell(1, 1));
+        assert_eq!(world.states.back(), Some(&world.world));
+
+        world.set_cell(1, 1, true);
+        world.set_cell(2, 0, false);
+        assert_eq!(world.population(), 1);
+
+        // a lone cell dies, then the empty world repeats itself
+        world.advance();
+        world.advance();
+        assert!(world.is_stable());
+
+        world.toggle_cell(0, 2);
+        assert!(!world.is_stable());
+        assert!(world.cell(0, 2));
 
-        let _world = super::World::new(Zero::zero(),world_size).unwrap();
+        world.toggle_cell(0, 2);
+        assert!(!world.cell(0, 2));
+        assert_eq!(world.world, Zero::zero());
     }
 
     #[test]
-    #[should_panic]
-    fn new_world_size_lesser_than_one() {
+    fn set_cell_starts_a_new_history() {
 
-        let world_size = 0;
+        let mut world = super::World::new(BigUint::from(1u8) << 12u8, 5).unwrap();
+        world.advance();
+
+        // the diagonal shrinks to its centre cell, which then dies, unlike the states recorded before the edit
+        for i in 1..4 { world.set_cell(i, i, true); }
+
+        assert_eq!(world.history_len(), 1);
+        assert_eq!(world.generation(), 1);
+
+        world.advance();
+        assert_eq!(world.stability(), super::Stability::Active);
+        assert_eq!(world.cycle_info(), None);
+        assert_eq!(world.live_cells().collect::<Vec<_>>(), vec![(2, 2)]);
 
-        let _world = super::World::new(Zero::zero(),world_size).unwrap();
+        world.advance();
+        world.advance();
+        assert!(world.is_extinct());
+        assert_eq!(world.stability(), super::Stability::StillLife);
+        assert_eq!(world.cycle_info(), Some((3, 4)));
+        world.check_invariants();
     }
 
     #[test]
-    fn is_stable_returns_correct_value() {
+    #[should_panic(expected = "outside of the 3 x 3 world")]
+    fn set_cell_panics_outside_of_the_world() {
 
-        let mut world = super::World{ world_size: 1, world: Zero::zero(), states: vec![Zero::zero()], stable: false };
+        let mut world = super::World::new(Zero::zero(), 3).unwrap();
 
-        assert_eq!(world.is_stable(), false);
+        world.set_cell(0, 3, true);
+    }
 
-        world.stable = true;
+    #[test]
+    #[should_panic(expected = "outside of the 3 x 3 world")]
+    fn cell_panics_outside_of_the_world() {
+
+        let seed = BigUint::parse_bytes(b"1000000000",2).unwrap();
+        let world = super::World::from_state(seed, 3, 3);
 
-        assert_eq!(world.is_stable(),  true);
+        world.cell(0, 3);
     }
 
     #[test]
-    fn get_cell_returns_correct_value() {
-        let seed = BigUint::parse_bytes(b"010010001",2).unwrap();
-        let world = super::World{ world_size: 3, world: seed.clone(), states: vec![seed], stable: false };
+    #[should_panic]
+    fn check_invariants_detects_cells_outside_of_the_grid() {
 
-        assert_eq!(world.get_cell(0),1);
-        assert_eq!(world.get_cell(1),0);
-        assert_eq!(world.get_cell(2),0);
-        assert_eq!(world.get_cell(3),0);
-        assert_eq!(world.get_cell(4),1);
-        assert_eq!(world.get_cell(5),0);
-        assert_eq!(world.get_cell(6),0);
-        assert_eq!(world.get_cell(7),1);
-        assert_eq!(world.get_cell(8),0);
+        let seed = BigUint::from(1u16 << 9);
+        let world = super::World::from_state(seed, 3, 3);
+
+        world.check_invariants();
     }
 
     #[test]
@@ -203,7 +3531,7 @@ mod tests {
         */
         
         let seed = BigUint::parse_bytes(b"0001001110100111",2).unwrap();
-        let world = super::World{ world_size: 4, world: seed.clone(), states: vec![seed], stable: false };
+        let world = super::World::from_state(seed, 4, 4);
 
         assert_eq!(world.count_nearby_cells(0),  2);
         assert_eq!(world.count_nearby_cells(1),  3);
@@ -223,6 +3551,445 @@ mod tests {
         assert_eq!(world.count_nearby_cells(15), 0);
     }
 
+    #[test]
+    fn rectangular_worlds_count_and_render_correctly() {
+
+        /*
+            10
+            11      11001
+            01      01101
+            00
+            11
+        */
+
+        let mut tall = super::World::new_rect(BigUint::parse_bytes(b"1100101101",2).unwrap(), 2, 5).unwrap();
+        let mut wide = super::World::new_rect(BigUint::parse_bytes(b"1011010011",2).unwrap(), 5, 2).unwrap();
+
+        assert_eq!(tall.render_counts(), "\
+┌────┐
+|*2 3|
+|*3*3|
+| 3*2|
+| 3 3|
+|*1*1|
+└────┘
+");
+
+        assert_eq!(wide.render_counts(), "\
+┌──────────┐
+|*2*3 3 3*1|
+| 3*3*2 3*1|
+└──────────┘
+");
+
+        assert_eq!(wide.to_string(), "\
+┌──────────┐
+|████    ██|
+|  ████  ██|
+└──────────┘
+");
+
+        for _ in 0..3 {
+
+            tall.advance();
+            wide.advance();
+
+            let mut transposed: Vec<(u16, u16)> = tall.live_coordinates().into_iter().map(|(row, col)| (col, row)).collect();
+            transposed.sort();
+
+            assert_eq!(wide.live_coordinates(), transposed);
+        }
+
+        assert!(super::World::new_rect(Zero::zero(), 51, 2).is_err());
+        assert!(super::World::new_rect(Zero::zero(), 2, 0).is_err());
+    }
+
+    #[test]
+    fn count_nearby_cells_ignores_bits_outside_of_the_grid() {
+
+        /*
+            000
+            000
+            000
+            111 <- outside of the grid
+        */
+
+        let seed = BigUint::parse_bytes(b"111000000000",2).unwrap();
+        let world = super::World::from_state(seed, 3, 3);
+
+        assert_eq!(world.count_nearby_cells(6), 0);
+        assert_eq!(world.count_nearby_cells(7), 0);
+        assert_eq!(world.count_nearby_cells(8), 0);
+    }
+
+    #[test]
+    fn count_nearby_cells_wraps_around_edges() {
+
+        /*
+            1001
+            0000
+            0000
+            1001
+        */
+
+        let seed = BigUint::parse_bytes(b"1001000000001001",2).unwrap();
+        let mut world = super::World::from_state(seed, 4, 4);
+
+        assert_eq!(world.count_nearby_cells(0), 0);
+
+        world.wrap = true;
+
+        assert_eq!(world.count_nearby_cells(0),  3);
+        assert_eq!(world.count_nearby_cells(3),  3);
+        assert_eq!(world.count_nearby_cells(12), 3);
+        assert_eq!(world.count_nearby_cells(15), 3);
+        assert_eq!(world.count_nearby_cells(4),  2);
+        assert_eq!(world.count_nearby_cells(5),  1);
+    }
+
+    #[test]
+    fn glider_loops_on_wrapping_world() {
+
+        let mut world = super::World::new_wrapping(Zero::zero(), 10).unwrap();
+        world.overlay(&super::World::from_state(BigUint::parse_bytes(b"111100010",2).unwrap(), 3, 3), 7, 7);
+
+        let seed = world.world.clone();
+
+        for _ in 0..39 { world.advance(); }
+
+        assert!(!world.is_stable());
+        assert_eq!(world.world.count_ones(), 5);
+        assert_ne!(world.world, seed);
+
+        world.advance();
+
+        assert!(world.is_stable());
+        assert_eq!(world.cycle_info(), Some((0, 40)));
+
+        let mut active = super::WorldBuilder::new().size(10).wrapping(true).active_set(true).build().unwrap();
+        active.overlay(&super::World::from_state(BigUint::parse_bytes(b"111100010",2).unwrap(), 3, 3), 7, 7);
+
+        while !active.is_stable() { active.advance(); }
+
+        assert_eq!(active.cycle_info(), Some((0, 40)));
+    }
+
+    #[test]
+    fn diff_returns_born_and_died_cells() {
+
+        /*
+            000  ->  010
+            111      010
+            000      010
+        */
+
+        let mut world = super::World::new(BigUint::parse_bytes(b"000111000",2).unwrap(), 3).unwrap();
+        let previous = world.clone();
+
+        world.advance();
+
+        let diff = previous.diff(&world).unwrap();
+
+        assert_eq!(diff.born, vec![(1, 0), (1, 2)]);
+        assert_eq!(diff.died, vec![(0, 1), (2, 1)]);
+
+        assert_eq!(world.diff(&previous).unwrap(), super::Diff { born: diff.died, died: diff.born });
+        assert_eq!(world.diff(&world).unwrap(), super::Diff::default());
+        assert!(world.diff(&super::World::new(Zero::zero(), 4).unwrap()).is_err());
+    }
+
+    #[test]
+    fn seed_recreates_the_current_generation() {
+
+        let mut world = super::World::new(BigUint::parse_bytes(b"0000000000001110010000010",2).unwrap(), 5).unwrap();
+
+        for _ in 0..3 { world.advance(); }
+
+        let seed: BigUint = world.seed().to_string().parse().unwrap();
+        let mut restored = super::World::new(seed, 5).unwrap();
+
+        assert_eq!(restored.to_string(), world.to_string());
+
+        world.advance();
+        restored.advance();
+
+        assert_eq!(restored.seed(), world.seed());
+    }
+
+    #[test]
+    fn rotate_90_four_times_returns_the_original() {
+
+        /*
+            01000
+            00100
+            11100
+            00000
+        */
+
+        let seed = BigUint::parse_bytes(b"00000001110010000010",2).unwrap();
+        let world = super::World::from_state(seed.clone(), 5, 4);
+
+        let rotated = world.rotate_90();
+
+        assert_eq!((rotated.width(), rotated.height()), (4, 5));
+        assert_eq!(rotated.live_cells().collect::<Vec<_>>(), vec![(1, 0), (1, 1), (3, 1), (1, 2), (2, 2)]);
+
+        assert_eq!(rotated.rotate_90().rotate_90().rotate_90().world, seed);
+        assert_eq!(world.flip_horizontal().flip_horizontal().world, seed);
+        assert_eq!(world.flip_horizontal().live_cells().collect::<Vec<_>>(), vec![(3, 0), (2, 1), (2, 2), (3, 2), (4, 2)]);
+    }
+
+    #[test]
+    fn translated_moves_every_live_cell() {
+
+        let seed = BigUint::parse_bytes(b"00000001110010000010",2).unwrap();
+        let world = super::World::from_state(seed, 5, 4);
+
+        let cells: Vec<(u16, u16)> = world.live_cells().collect();
+
+        let right: Vec<(u16, u16)> = world.translated(1, 0).live_cells().collect();
+        assert_eq!(right, cells.iter().map(|&(x, y)| (x + 1, y)).collect::<Vec<_>>());
+
+        assert_eq!(world.translated(3, 0).population(), 3);
+        assert_eq!(world.translated(0, -1000).population(), 0);
+
+        let mut wrapping = world.clone();
+        wrapping.wrap = true;
+
+        assert_eq!(wrapping.translated(5, -12).world, world.world);
+        assert_eq!(wrapping.translated(3, 0).population(), 5);
+    }
+
+    #[test]
+    fn render_uses_custom_glyphs() {
+
+        let seed = BigUint::parse_bytes(b"010100",2).unwrap();
+        let world = super::World::from_state(seed, 3, 2);
+
+        let options = super::RenderOptions { alive: String::from("#"), dead: String::from("."), ..Default::default() };
+
+        assert_eq!(world.render(&options), "\
+┌───┐
+|..#|
+|.#.|
+└───┘");
+
+        let options = super::RenderOptions { border: false, alive_color: Some(String::from("32")), ..options };
+
+        assert_eq!(world.render(&options), "..\x1B[32m#\x1B[0m\n.\x1B[32m#\x1B[0m.");
+        assert_eq!(world.to_string(), format!("{}\n", world.render(&Default::default())));
+    }
+
+    #[test]
+    fn render_counts_shows_neighbour_counts() {
+
+        let seed = BigUint::parse_bytes(b"0001001110100111",2).unwrap();
+        let world = super::World::from_state(seed, 4, 4);
+
+        assert_eq!(world.render_counts(), "\
+┌────────┐
+|*2*3*3 2|
+| 5*5 5*1|
+|*3*3 3 1|
+|*2 3 1 0|
+└────────┘
+");
+    }
+
+    #[test]
+    fn sync_step_matches_brute_force_on_random_boards() {
+
+        use rand::{ Rng, SeedableRng, rngs::StdRng };
+
+        let mut rng = StdRng::seed_from_u64(263);
+
+        for _ in 0..200 {
+
+            let (width, height) = (rng.gen_range(1..=12u8), rng.gen_range(1..=12u8));
+            let area = width as u64 * height as u64;
+
+            let mut seed: BigUint = Zero::zero();
+            for i in 0..area + 3 { seed.set_bit(i, rng.gen_bool(0.4)); }
+
+            let mut world = super::World::from_state(seed.clone(), width, height);
+            world.wrap = rng.gen_bool(0.5);
+            world.rule = if rng.gen_bool(0.5) { super::Rule::default() } else { super::Rule::parse("B36/S23").unwrap() };
+
+            let (w, h) = (width as i32, height as i32);
+            let mut expected = seed.clone();
+
+            for row in 0..h {
+                for col in 0..w {
+
+                    let mut count = 0;
+
+                    for (d_row, d_col) in [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)] {
+
+                        let (r, c) = if world.wrap {
+                            ((row + d_row).rem_euclid(h), (col + d_col).rem_euclid(w))
+                        }
+                        else {
+                            (row + d_row, col + d_col)
+                        };
+
+                        if (0..h).contains(&r) && (0..w).contains(&c) && seed.bit((r * w + c) as u64) {
+                            count += 1;
+                        }
+                    }
+
+                    let index = (row * w + col) as u64;
+                    let counts = if seed.bit(index) { world.rule.survival } else { world.rule.birth };
+
+                    expected.set_bit(index, counts[count]);
+                }
+            }
+
+            assert_eq!(world.sync_step(), expected, "{} x {} world, wrap {}", width, height, world.wrap);
+
+            world.advance();
+            assert_eq!(world.world, expected);
+        }
+    }
+
+    #[test]
+    fn random_fills_cells_with_the_given_density() {
+
+        use rand::{ SeedableRng, rngs::StdRng };
+
+        let mut rng = StdRng::seed_from_u64(265);
+
+        assert_eq!(super::World::random(50, 0.0, &mut rng).unwrap().population(), 0);
+        assert_eq!(super::World::random(50, 1.0, &mut rng).unwrap().population(), 2500);
+
+        let population = super::World::random(50, 0.5, &mut rng).unwrap().population();
+        assert!((1150..=1350).contains(&population), "{} cells alive", population);
+
+        assert_eq!(super::World::random(60, 0.5, &mut rng).err(), Some(super::WorldError::SizeTooLarge { max: 50, got: 60 }));
+        assert_eq!(super::World::random(0, 0.5, &mut rng).err(), Some(super::WorldError::SizeZero));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip_continues_identically() {
+
+        /*
+            01000
+            00100
+            11100
+            00000
+            00000
+        */
+
+        let seed = BigUint::parse_bytes(b"0000000000001110010000010",2).unwrap();
+        let mut world = super::WorldBuilder::new().seed(seed).size(5).wrapping(true).max_history(Some(32)).build().unwrap();
+
+        for _ in 0..36 { world.advance(); }
+
+        let mut restored = super::World::from_json(&world.to_json()).unwrap();
+
+        assert_eq!(restored.states, world.states);
+
+        while !world.is_stable() {
+
+            world.advance();
+            restored.advance();
+
+            assert_eq!(restored.world, world.world);
+        }
+
+        assert!(restored.is_stable());
+        assert_eq!(restored.cycle_info(), world.cycle_info());
+
+        assert!(super::World::from_json("{}").is_err());
+        assert!(super::World::from_json(&world.to_json().replace("\"width\":5", "\"width\":0")).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trips_after_going_back() {
+
+        let mut world = super::World::from_state(BigUint::parse_bytes(b"011010011",2).unwrap(), 3, 3);
+        while !world.is_stable() { world.advance(); }
+
+        assert!(world.rewind_to_unique());
+        assert_eq!(super::World::from_json(&world.to_json()).unwrap().states, world.states);
+
+        // rotates every block clockwise
+        let mut table = [0u8; 16];
+        for (block, next) in table.iter_mut().enumerate() {
+            let block = block as u8;
+            *next = ((block & 1) << 1) | ((block & 2) << 2) | ((block & 8) >> 1) | ((block & 4) >> 2);
+        }
+
+        // the lone cell moves to the top edge, which odd steps leave untouched, so the world repeats its last state
+        let mut world = super::World::from_state(BigUint::from(1u8), 4, 4);
+        world.set_update_mode(super::UpdateMode::Margolus { table });
+        while !world.is_stable() { world.advance(); }
+
+        assert_eq!(world.period(), Some(1));
+
+        world.advance_reverse().unwrap();
+        assert_eq!(super::World::from_json(&world.to_json()).unwrap().states, world.states);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn from_json_rejects_inconsistent_states() {
+
+        let mut world = super::World::new(BigUint::from(0b111000u8), 3).unwrap();
+        world.advance();
+        world.advance();
+
+        let json = world.to_json();
+        let state: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        let corrupted = |field: &str, value: serde_json::Value| {
+            let mut state = state.clone();
+            state[field] = value;
+            super::World::from_json(&state.to_string())
+        };
+
+        assert!(super::World::from_json(&json).is_ok());
+        assert!(corrupted("states", serde_json::json!([])).is_err());
+        assert!(corrupted("states", serde_json::json!(["38"])).is_err());
+        assert!(corrupted("loop_start", serde_json::json!(99)).is_err());
+        assert!(corrupted("loop_start", serde_json::Value::Null).is_err());
+        assert!(corrupted("world", serde_json::json!("38")).is_err());
+        assert!(corrupted("states", serde_json::json!(["200", "38"])).is_err());
+        assert!(corrupted("max_history", serde_json::json!(1)).is_err());
+        assert!(corrupted("max_history", serde_json::json!(0)).is_err());
+        assert!(corrupted("initial", serde_json::json!("92")).is_err());
+        assert!(corrupted("changed", serde_json::json!("200")).is_err());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn advance_parallel_matches_advance() {
+
+        use rand::{ Rng, SeedableRng, rngs::StdRng };
+
+        let mut rng = StdRng::seed_from_u64(264);
+
+        let mut seed: BigUint = Zero::zero();
+        for i in 0..50 * 50 { seed.set_bit(i, rng.gen_bool(0.4)); }
+
+        for wrap in [false, true] {
+
+            let mut serial = super::World::from_state(seed.clone(), 50, 50);
+            serial.wrap = wrap;
+            let mut parallel = serial.clone();
+
+            for _ in 0..30 {
+
+                serial.advance();
+                parallel.advance_parallel();
+
+                assert_eq!(parallel.world, serial.world);
+                assert_eq!(parallel.states, serial.states);
+            }
+        }
+    }
+
     #[test]
     fn advance_works_correctly() {
         
@@ -233,7 +4000,7 @@ mod tests {
         */
 
         let seed = BigUint::parse_bytes(b"011010011",2).unwrap();
-        let mut world = super::World{ world_size: 3, world: seed.clone(), states: vec![seed.clone()], stable: false };
+        let mut world = super::World::from_state(seed.clone(), 3, 3);
 
         assert_eq!(world.world, seed);
 
@@ -251,6 +4018,6 @@ mod tests {
 
         world.advance();
         assert_eq!(world.world, Zero::zero());
-        assert_eq!(world.stable, true);
+        assert!(world.stable);
     }
 }
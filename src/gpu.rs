@@ -0,0 +1,244 @@
+use crate::game_of_life::{ Rule, World };
+
+use wgpu::util::DeviceExt;
+
+const WORKGROUP_SIZE: u32 = 8;
+
+/// A world stepped by a compute shader, with the same edges and rule as the `World` it was created from.
+/// The grid only leaves the GPU when `to_grid` is called, so there is no history or stability detection.
+pub struct GpuWorld {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    bind_groups: [wgpu::BindGroup; 2],
+    cells: [wgpu::Buffer; 2],
+    readback: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    current: usize
+}
+
+impl GpuWorld {
+
+    /// Uploads a bounded `world_size` x `world_size` grid given in reading order, run under Conway's rule.
+    /// Returns `None` if no GPU adapter is available.
+    pub fn new(world_size: u32, grid: &[bool]) -> Option<GpuWorld> {
+        GpuWorld::with_edges(world_size, world_size, grid, false, Rule::default())
+    }
+
+    pub fn from_world(world: &World) -> Option<GpuWorld> {
+
+        let (width, height) = (world.width() as u32, world.height() as u32);
+        let grid: Vec<bool> = (0..width * height).map(|i| world.get_cell(i as u16) == 1).collect();
+
+        GpuWorld::with_edges(width, height, &grid, world.is_wrapping(), world.rule())
+    }
+
+    fn with_edges(width: u32, height: u32, grid: &[bool], wrap: bool, rule: Rule) -> Option<GpuWorld> {
+
+        assert_eq!(grid.len(), (width * height) as usize, "grid does not match the world size");
+
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default())).ok()?;
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default())).ok()?;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("game of life"),
+            source: wgpu::ShaderSource::Wgsl(shader_source(width, height, wrap, &rule).into())
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("game of life"),
+            layout: None,
+            module: &shader,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None
+        });
+
+        let contents: Vec<u8> = grid.iter().flat_map(|&alive| (alive as u32).to_le_bytes()).collect();
+        let usage = wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST;
+
+        let cells = [
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: Some("cells a"), contents: &contents, usage }),
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: Some("cells b"), contents: &contents, usage })
+        ];
+
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: contents.len() as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+
+        let bind_group = |current: &wgpu::Buffer, next: &wgpu::Buffer| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: current.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 1, resource: next.as_entire_binding() }
+                ]
+            })
+        };
+
+        let bind_groups = [bind_group(&cells[0], &cells[1]), bind_group(&cells[1], &cells[0])];
+
+        Some(GpuWorld { device, queue, pipeline, bind_groups, cells, readback, width, height, current: 0 })
+    }
+
+    pub fn advance(&mut self) {
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.bind_groups[self.current], &[]);
+            pass.dispatch_workgroups(self.width.div_ceil(WORKGROUP_SIZE), self.height.div_ceil(WORKGROUP_SIZE), 1);
+        }
+
+        self.queue.submit(Some(encoder.finish()));
+        self.current = 1 - self.current;
+    }
+
+    /// Reads the grid back from the GPU, in reading order.
+    pub fn to_grid(&self) -> Vec<bool> {
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(&self.cells[self.current], 0, &self.readback, 0, self.readback.size());
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = self.readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |result| result.expect("could not read the grid back"));
+        self.device.poll(wgpu::PollType::wait_indefinitely()).expect("could not read the grid back");
+
+        let grid = slice.get_mapped_range().expect("could not read the grid back")
+            .chunks_exact(4)
+            .map(|cell| cell != [0, 0, 0, 0])
+            .collect();
+
+        self.readback.unmap();
+
+        grid
+    }
+}
+
+fn shader_source(width: u32, height: u32, wrap: bool, rule: &Rule) -> String {
+
+    let mask = |counts: &[bool; 9]| (0..9).filter(|&i| counts[i]).fold(0u32, |mask, i| mask | 1 << i);
+
+    format!("
+const WIDTH: i32 = {width};
+const HEIGHT: i32 = {height};
+const WRAP: bool = {wrap};
+const BIRTH: u32 = {birth}u;
+const SURVIVAL: u32 = {survival}u;
+
+@group(0) @binding(0) var<storage, read> current: array<u32>;
+@group(0) @binding(1) var<storage, read_write> next: array<u32>;
+
+@compute @workgroup_size({workgroup}, {workgroup})
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {{
+
+    let x = i32(id.x);
+    let y = i32(id.y);
+
+    if (x >= WIDTH || y >= HEIGHT) {{ return; }}
+
+    var count = 0u;
+
+    for (var d_y = -1; d_y <= 1; d_y++) {{
+        for (var d_x = -1; d_x <= 1; d_x++) {{
+
+            var n_x = x + d_x;
+            var n_y = y + d_y;
+
+            if (WRAP) {{
+                n_x = (n_x + WIDTH) % WIDTH;
+                n_y = (n_y + HEIGHT) % HEIGHT;
+            }}
+
+            if ((d_x != 0 || d_y != 0) && n_x >= 0 && n_y >= 0 && n_x < WIDTH && n_y < HEIGHT) {{
+                count += current[n_y * WIDTH + n_x];
+            }}
+        }}
+    }}
+
+    let index = y * WIDTH + x;
+    let alive = current[index] == 1u;
+
+    let counts = select(BIRTH, SURVIVAL, alive);
+
+    next[index] = (counts >> count) & 1u;
+}}
+", width = width, height = height, wrap = wrap, birth = mask(&rule.birth), survival = mask(&rule.survival), workgroup = WORKGROUP_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::game_of_life::{ Rule, World };
+
+    use num_bigint::BigUint;
+
+    #[test]
+    #[ignore = "needs a GPU adapter, run with --ignored"]
+    fn gpu_matches_cpu() {
+
+        let blinker = World::new(BigUint::parse_bytes(b"0000000100001000010000000",2).unwrap(), 5).unwrap();
+
+        let mut glider = World::new(BigUint::from(0u8), 10).unwrap();
+        glider.overlay(&World::new(BigUint::parse_bytes(b"111100010",2).unwrap(), 3).unwrap(), 0, 0);
+
+        let mut wrapping = World::new_wrapping(BigUint::from(0u8), 10).unwrap();
+        wrapping.overlay(&World::new(BigUint::parse_bytes(b"111100010",2).unwrap(), 3).unwrap(), 7, 7);
+
+        let mut rect = World::new_rect(BigUint::from(0u8), 12, 6).unwrap();
+        rect.overlay(&World::new(BigUint::parse_bytes(b"111100010",2).unwrap(), 3).unwrap(), 0, 0);
+
+        let high_life = World::new_with_rule(BigUint::parse_bytes(b"111000111",2).unwrap(), 3, Rule::parse("B36/S23").unwrap())
+            .unwrap()
+            .with_margin(4);
+
+        for mut world in [blinker, glider, wrapping, rect, high_life] {
+
+            let mut gpu = super::GpuWorld::from_world(&world).expect("no GPU adapter available");
+
+            loop {
+
+                world.advance();
+                gpu.advance();
+
+                // a world that detects a cycle keeps its last state rather than stepping into the repeat
+                if world.is_stable() { break; }
+
+                let area = world.width() as u16 * world.height() as u16;
+                let cpu: Vec<bool> = (0..area).map(|i| world.get_cell(i) == 1).collect();
+
+                assert_eq!(gpu.to_grid(), cpu);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn gpu_benchmark() {
+
+        use rand::{ Rng, SeedableRng, rngs::StdRng };
+        use std::time::Instant;
+
+        let world_size = 1024;
+        let mut rng = StdRng::seed_from_u64(1);
+        let grid: Vec<bool> = (0..world_size * world_size).map(|_| rng.gen_bool(0.5)).collect();
+
+        let mut gpu = super::GpuWorld::new(world_size, &grid).expect("no GPU adapter available");
+
+        let start = Instant::now();
+
+        for _ in 0..1000 { gpu.advance(); }
+        let population = gpu.to_grid().iter().filter(|&&alive| alive).count();
+
+        println!("1000 generations of a {0}x{0} world: {1:?}, final population {2}", world_size, start.elapsed(), population);
+    }
+}
@@ -0,0 +1,10 @@
+pub mod editor;
+pub mod game_of_life;
+pub mod patterns;
+pub mod rle;
+
+#[cfg(feature = "gpu")]
+pub mod gpu;
+
+#[cfg(feature = "tui")]
+pub mod tui;
@@ -1,56 +1,283 @@
 /*
-Rules:
+Rules (default, B3/S23):
 - less than two cells nearby: cell dies
 - two or three cells nearby: cell survives
 - more than three cells nearby: cell dies
 - three cells nearby: a new cell grows
 */
 
-mod game_of_life;
+use rust_game_of_life::game_of_life::{ RenderOptions, Rule, Stability, World, WorldError };
+use rust_game_of_life::{ patterns, rle };
 
-use game_of_life::World;
-
-use std::{ thread, time, env, process };
+use std::{ thread, time, env, process, fs };
+use std::path::Path;
 
 use num_bigint::BigUint;
 use num_traits::Zero;
+use rand::{ SeedableRng, rngs::StdRng };
+
+// shortest --interval used for --html, a 0 ms delay would keep the browser busy redrawing
+const MIN_HTML_FRAME_MS: u64 = 16;
+
+enum Pattern {
+    Checkerboard,
+    Stripes,
+    File(String),
+    Rle(String),
+    Random(f64),
+    Preset(patterns::Pattern)
+}
+
+struct Params {
+    seed: BigUint,
+    world_size: u8,
+    pattern: Option<Pattern>,
+    random_seed: bool,
+    html: Option<String>,
+    margin: u8,
+    dump_objects: Option<String>,
+    rule: Rule,
+    rng_seed: Option<u64>,
+    render: RenderOptions,
+    headless: bool,
+    max_generations: Option<u64>,
+    interval_ms: u64,
+    show_seed: bool,
+    #[cfg(feature = "image")]
+    png: Option<String>,
+    #[cfg(feature = "tui")]
+    tui: bool
+}
 
 fn main() {
 
-    let interval = time::Duration::from_millis(500);
+    let mut params = parse_params_from_args();
+
+    let interval = time::Duration::from_millis(params.interval_ms);
 
-    let (seed, world_size) = parse_params_from_args();
+    if params.random_seed {
+        params.seed = random_seed(params.world_size);
+        println!("Seed: {}", params.seed);
+    }
 
-    let mut world = World::new(seed, world_size).unwrap();
+    let random = match params.pattern {
+        Some(Pattern::Random(density)) => {
+            let rng_seed = params.rng_seed.unwrap_or_else(rand::random);
+            println!("Random seed: {}", rng_seed);
+            Some((density, rng_seed))
+        },
+        _ => None
+    };
 
-    while !world.is_stable() {
+    let world = match params.pattern {
+        Some(Pattern::Random(density)) => {
+            let (_, rng_seed) = random.unwrap();
+            World::random(params.world_size, density, &mut StdRng::seed_from_u64(rng_seed))
+        },
+        Some(Pattern::Checkerboard) => World::checkerboard(params.world_size),
+        Some(Pattern::Stripes) => World::stripes(params.world_size, 1),
+        Some(Pattern::Preset(pattern)) => place_in_corner(&pattern, params.world_size),
+        Some(Pattern::Rle(path)) => match load_rle(&path, params.world_size) {
+            Ok(world) => Ok(world),
+            Err(e) => {
+                eprintln!("Could not load {}: {}", path, e);
+                process::exit(1);
+            }
+        },
+        Some(Pattern::File(path)) => match fs::read_to_string(&path) {
+            Ok(grid) => World::from_ascii(&grid, params.world_size),
+            Err(e) => {
+                eprintln!("Could not read {}: {}", path, e);
+                process::exit(1);
+            }
+        },
+        None => World::new(params.seed.clone(), params.world_size)
+    };
 
-        print!("\x1B[2J\x1B[1;1H");   //clear screen and potision cursor at 1;1
-        print!("{}", world);
+    let mut world = match world {
+        Ok(world) => world,
+        Err(e) => {
+            eprintln!("Could not create the world: {}", e);
+            process::exit(1);
+        }
+    };
+
+    world.set_rule(params.rule);
+    let mut world = world.with_margin(params.margin);
+
+    #[cfg(feature = "tui")]
+    if params.tui {
+        match rust_game_of_life::tui::run(world, interval) {
+            Ok(world) => print!("{}", world),
+            Err(e) => {
+                eprintln!("Terminal error: {}", e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let mut generations = 0;
+
+    while !world.is_stable() && params.max_generations.is_none_or(|max| generations < max) {
+
+        if !params.headless {
+            print!("\x1B[2J\x1B[1;1H");   //clear screen and potision cursor at 1;1
+            println!("{}", world.render(&params.render));
+            println!("Population: {}", world.population());
+            if params.show_seed { print_seed(&world); }
+        }
         
         world.advance();
+        generations += 1;
+
+        if !params.headless && !interval.is_zero() { thread::sleep(interval); }
+    }
+
+    if params.headless {
+        println!("{}", world.render(&params.render));
+        println!("Population: {}", world.population());
+        if params.show_seed { print_seed(&world); }
+    }
+
+    match world.stability() {
+        Stability::Active => println!("World is not stable after {} generations", generations),
+        _ if world.is_extinct() => println!("World is extinct"),
+        Stability::StillLife => println!("World is a still life"),
+        Stability::Oscillating { period } => println!("World is an oscillator with period {}", period)
+    }
+
+    if let Some((start, current)) = world.cycle_info() {
+        println!("Cycle detected: generation {} matches generation {}, period {}", current, start, current - start);
+    }
+
+    if let Some(path) = params.html {
+        if let Err(e) = fs::write(&path, world.to_html(params.interval_ms.max(MIN_HTML_FRAME_MS))) {
+            eprintln!("Could not write {}: {}", path, e);
+            process::exit(1);
+        }
+        println!("Animation written to {}", path);
+    }
+
+    #[cfg(feature = "image")]
+    if let Some(path) = params.png {
+        if let Err(e) = world.save_png(Path::new(&path), 10) {
+            eprintln!("Could not write {}: {}", path, e);
+            process::exit(1);
+        }
+        println!("Final world written to {}", path);
+    }
+
+    if let Some(dir) = params.dump_objects {
+        if let Err(e) = dump_objects(&world, &dir) {
+            eprintln!("Could not write objects to {}: {}", dir, e);
+            process::exit(1);
+        }
+    }
 
-        thread::sleep(interval);
+    if params.random_seed {
+        println!("Run again with --seed {} to reproduce", params.seed);
     }
 
-    println!("World is stable");
+    if let Some((density, rng_seed)) = random {
+        println!("Run again with --random {} --rng-seed {} to reproduce", density, rng_seed);
+    }
 }
 
-fn parse_params_from_args() -> (BigUint, u8) {
+fn print_seed(world: &World) {
+    println!("Seed: {} (0x{:x})", world.seed(), world.seed());
+}
+
+fn load_rle(path: &str, world_size: u8) -> Result<World, String> {
+
+    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let (cells, width, height) = rle::parse_rle(&contents).map_err(|e| e.to_string())?;
+
+    place_in_corner(&patterns::Pattern { cells, width, height }, world_size).map_err(|e| e.to_string())
+}
+
+/// Places the pattern at the top-left corner of the world, growing it to fit the pattern if needed.
+fn place_in_corner(pattern: &patterns::Pattern, world_size: u8) -> Result<World, WorldError> {
+
+    let mut world = World::new_rect(Zero::zero(), pattern.width.max(world_size), pattern.height.max(world_size))?;
+
+    patterns::place(&mut world, pattern, 0, 0)?;
+
+    Ok(world)
+}
+
+fn dump_objects(world: &World, dir: &str) -> std::io::Result<()> {
+
+    let objects = world.export_components();
+
+    fs::create_dir_all(dir)?;
+
+    for (i, (_, rle)) in objects.iter().enumerate() {
+        fs::write(Path::new(dir).join(format!("object_{}.rle", i + 1)), rle)?;
+    }
+
+    println!("{} objects written to {}", objects.len(), dir);
+
+    Ok(())
+}
+
+fn random_seed(world_size: u8) -> BigUint {
+
+    let mut state = time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+
+    let mut seed: BigUint = Zero::zero();
+    let mut bits = 0;
+
+    for i in 0..(world_size as u64).pow(2) {
+
+        if i % 64 == 0 {
+            // splitmix64
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            bits = (state ^ (state >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            bits = (bits ^ (bits >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            bits ^= bits >> 31;
+        }
+
+        seed.set_bit(i, (bits >> (i % 64)) & 1 == 1);
+    }
+
+    seed
+}
+
+fn parse_params_from_args() -> Params {
 
     let args: Vec<String> = env::args().collect();
-    let mut iter = args.iter().skip(1);
+    let mut iter = args.iter().skip(1).peekable();
 
     let mut wrong_usage = false;
     let mut seed: BigUint = Zero::zero();
     let mut world_size: u8 = 3;
+    let mut pattern = None;
+    let mut random_seed = false;
+    let mut html = None;
+    let mut margin = 0;
+    let mut dump_objects = None;
+    let mut rule = Rule::default();
+    let mut rng_seed = None;
+    let mut render = RenderOptions::default();
+    let mut headless = false;
+    let mut max_generations = None;
+    let mut interval_ms = 500;
+    let mut show_seed = false;
+    #[cfg(feature = "image")]
+    let mut png = None;
+    #[cfg(feature = "tui")]
+    let mut tui = false;
 
     while let Some(arg) = iter.next() {
 
         match &arg[..] {
             
             "--seed" | "-s" => 
-                if let Ok(s) = iter.next().unwrap_or(&" ".to_string()).parse() {
+                if let Some(s) = iter.next().and_then(|s| parse_seed(s)) {
                     seed = s
                 }
                 else { wrong_usage = true; },
@@ -61,15 +288,150 @@ fn parse_params_from_args() -> (BigUint, u8) {
                 }
                 else { wrong_usage = true; },
 
+            "--pattern" | "-p" =>
+                match iter.next().map(|p| &p[..]) {
+                    Some("checkerboard") => pattern = Some(Pattern::Checkerboard),
+                    Some("stripes") => pattern = Some(Pattern::Stripes),
+                    _ => wrong_usage = true
+                },
+
+            "--preset" =>
+                match iter.next().and_then(|name| patterns::by_name(name)) {
+                    Some(preset) => pattern = Some(Pattern::Preset(preset)),
+                    None => wrong_usage = true
+                },
+
+            "--pattern-file" =>
+                if let Some(path) = iter.next() {
+                    pattern = Some(Pattern::File(path.clone()))
+                }
+                else { wrong_usage = true; },
+
+            "--rle" =>
+                if let Some(path) = iter.next() {
+                    pattern = Some(Pattern::Rle(path.clone()))
+                }
+                else { wrong_usage = true; },
+
+            "--interval" | "-i" =>
+                if let Ok(i) = iter.next().unwrap_or(&" ".to_string()).parse() {
+                    interval_ms = i
+                }
+                else { wrong_usage = true; },
+
+            "--margin" | "-m" =>
+                if let Ok(m) = iter.next().unwrap_or(&" ".to_string()).parse() {
+                    margin = m
+                }
+                else { wrong_usage = true; },
+
+            "--rule" | "-r" =>
+                if let Ok(r) = Rule::parse(iter.next().unwrap_or(&" ".to_string())) {
+                    rule = r
+                }
+                else { wrong_usage = true; },
+
+            "--random-seed" => random_seed = true,
+
+            "--headless" => headless = true,
+
+            #[cfg(feature = "tui")]
+            "--tui" => tui = true,
+
+            "--show-seed" => show_seed = true,
+
+            "--max-generations" =>
+                if let Ok(n) = iter.next().unwrap_or(&" ".to_string()).parse() {
+                    max_generations = Some(n)
+                }
+                else { wrong_usage = true; },
+
+            "--alive-char" =>
+                if let Some(glyph) = iter.next() {
+                    render.alive = glyph.clone()
+                }
+                else { wrong_usage = true; },
+
+            "--dead-char" =>
+                if let Some(glyph) = iter.next() {
+                    render.dead = glyph.clone()
+                }
+                else { wrong_usage = true; },
+
+            "--random" => {
+                let density = match iter.peek().and_then(|d| d.parse::<f64>().ok()) {
+                    Some(d) => { iter.next(); d },
+                    None => 0.5
+                };
+
+                if (0.0..=1.0).contains(&density) {
+                    pattern = Some(Pattern::Random(density))
+                }
+                else { wrong_usage = true; }
+            },
+
+            "--rng-seed" =>
+                if let Ok(s) = iter.next().unwrap_or(&" ".to_string()).parse() {
+                    rng_seed = Some(s)
+                }
+                else { wrong_usage = true; },
+
+            "--dump-objects" =>
+                if let Some(dir) = iter.next() {
+                    dump_objects = Some(dir.clone())
+                }
+                else { wrong_usage = true; },
+
+            #[cfg(feature = "image")]
+            "--png" =>
+                if let Some(path) = iter.next() {
+                    png = Some(path.clone())
+                }
+                else { wrong_usage = true; },
+
+            "--html" =>
+                if let Some(path) = iter.next() {
+                    html = Some(path.clone())
+                }
+                else { wrong_usage = true; },
+
             "--help" | "-h" => print_help_and_exit(0),
 
             _ => wrong_usage= true
         }
     }
 
-    if wrong_usage || seed == Zero::zero() { print_help_and_exit(1); }
+    // the editor can start from an empty world
+    #[cfg(feature = "tui")]
+    let seed_required = !tui;
+    #[cfg(not(feature = "tui"))]
+    let seed_required = true;
+
+    if wrong_usage || (seed_required && seed == Zero::zero() && pattern.is_none() && !random_seed) { print_help_and_exit(1); }
+
+    Params {
+        seed, world_size, pattern, random_seed, html, margin, dump_objects, rule, rng_seed, render, headless, max_generations, interval_ms, show_seed,
+        #[cfg(feature = "image")]
+        png,
+        #[cfg(feature = "tui")]
+        tui
+    }
+}
+
+/// Parses a seed written in decimal, or in hex or binary with a `0x` or `0b` prefix.
+fn parse_seed(seed: &str) -> Option<BigUint> {
+
+    let (digits, radix) = if let Some(hex) = seed.strip_prefix("0x").or_else(|| seed.strip_prefix("0X")) {
+        (hex, 16)
+    }
+    else if let Some(binary) = seed.strip_prefix("0b").or_else(|| seed.strip_prefix("0B")) {
+        (binary, 2)
+    }
+    else {
+        (seed, 10)
+    };
 
-    (seed, world_size)
+    BigUint::parse_bytes(digits.as_bytes(), radix)
 }
 
 fn print_help_and_exit(return_code: i32) {
@@ -87,12 +449,64 @@ Usage:
     {0} [OPTIONS] ...
 
 Command Line Arguments:
-    -s, --seed <num>        : REQUIRED, The initial value of the world, written as a decimal number
+    -s, --seed <num>        : REQUIRED (unless --pattern, --preset, --pattern-file, --rle, --random, --random-seed or --tui is used), The initial value of the world, written as a decimal number, or in hex (0x1A3) or binary (0b110100011)
     -w, --world-size <num>  : Side length of the square world (Default 3) 
+    -p, --pattern <name>    : Generate the initial world instead of using a seed (checkerboard, stripes)
+    --preset <name>         : Start from a known pattern at the top-left corner ({1})
+    --rle <path>            : Read the initial world from an RLE file, placed at the top-left corner
+    --pattern-file <path>   : Read the initial world from a text grid, one row per line, # O 1 alive and . 0 space dead
+    -i, --interval <ms>     : Delay between frames in milliseconds, 0 runs as fast as possible (Default 500)
+    -m, --margin <num>      : Surround the initial world with dead cells on every side (Default 0)
+    -r, --rule <rule>       : Birth and survival neighbour counts in B/S notation (Default B3/S23)
+    --random-seed           : Pick a random seed from the system clock and print it
+    --alive-char <glyph>    : Text drawn for live cells (Default ██)
+    --dead-char <glyph>     : Text drawn for dead cells, as wide as the live one (Default two spaces)
+    --random [density]      : Fill each cell alive with the given probability (Default 0.5)
+    --rng-seed <num>        : Seed the random number generator used by --random (Default picked by the OS)
+    --show-seed             : Print the seed of every generation, to restart from it with --seed and the same --world-size
+    --tui                   : Edit the world interactively: arrows move, space toggles, g stamps a glider, n steps, p plays, q quits (needs the tui feature)
+    --headless              : Run without animating and print only the final world
+    --max-generations <num> : Stop after this many generations even if the world is not stable
+    --html <path>           : Once the world is stable, save the whole run as an HTML animation, one frame every --interval (at least 16 ms)
+    --png <path>            : Once the world is stable, save it as a PNG image (needs the image feature)
+    --dump-objects <dir>    : Once the world is stable, save each object as a numbered RLE file
 
 Example:
     {0} -s 23 -w 4     : Creates a 4 x 4 world with the starting value of 23
-    ", filename);
+    {0} -p checkerboard -w 8 : Creates an 8 x 8 world filled with a checkerboard
+    {0} -s 23 -w 4 -r B36/S23 : Runs the same world under HighLife
+    {0} --preset gosper_gun -w 50 : Fires gliders from a Gosper glider gun
+    {0} --random 0.3 -w 20 : Creates a 20 x 20 world with about 30% of the cells alive
+    {0} --random -w 50 --headless --max-generations 1000 > result.txt : Saves where a random world ends up
+    {0} -s 23 -w 4 --alive-char '#' --dead-char . : Draws live cells as # and dead ones as .
+    ", filename, patterns::NAMES.join(", "));
 
     process::exit(return_code);
 }
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+    fn parse_seed_accepts_decimal_hex_and_binary() {
+
+        use num_bigint::BigUint;
+
+        assert_eq!(super::parse_seed("0b011010011"), Some(BigUint::from(211u16)));
+        assert_eq!(super::parse_seed("0x1A3"), Some(BigUint::from(419u16)));
+        assert_eq!(super::parse_seed("419"), Some(BigUint::from(419u16)));
+
+        assert_eq!(super::parse_seed("0xZZ"), None);
+        assert_eq!(super::parse_seed("0b012"), None);
+        assert_eq!(super::parse_seed("0x"), None);
+        assert_eq!(super::parse_seed("1A3"), None);
+    }
+
+    #[test]
+    fn random_seed_fits_in_world() {
+
+        for world_size in [1u8, 3, 8, 50] {
+            assert!(super::random_seed(world_size).bits() <= (world_size as u64).pow(2));
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::game_of_life::{ World, WorldError };
+use crate::rle;
+
+use num_bigint::BigUint;
+
+/// A pattern cropped to its bounding box, with its cells laid out row by row as in a world of the same size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub cells: BigUint,
+    pub width: u8,
+    pub height: u8
+}
+
+impl Pattern {
+
+    fn from_rle(rle: &str) -> Pattern {
+
+        let (cells, width, height) = rle::parse_rle(rle).unwrap();
+
+        Pattern { cells, width, height }
+    }
+}
+
+pub const NAMES: [&str; 3] = ["glider", "blinker", "gosper_gun"];
+
+pub fn glider() -> Pattern {
+    Pattern::from_rle("x = 3, y = 3\nbo$2bo$3o!")
+}
+
+pub fn blinker() -> Pattern {
+    Pattern::from_rle("x = 3, y = 1\n3o!")
+}
+
+/// Gosper's glider gun, firing a new glider every 30 generations.
+pub fn gosper_gun() -> Pattern {
+    Pattern::from_rle("\
+x = 36, y = 9
+24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$
+10bo5bo7bo$11bo3bo$12b2o!")
+}
+
+/// Looks up one of the patterns listed in `NAMES`.
+pub fn by_name(name: &str) -> Option<Pattern> {
+    match name {
+        "glider" => Some(glider()),
+        "blinker" => Some(blinker()),
+        "gosper_gun" => Some(gosper_gun()),
+        _ => None
+    }
+}
+
+/// Adds the live cells of `pattern` to the world with its top-left corner at column `x` and row `y`.
+/// Fails, leaving the world untouched, if the pattern does not fit entirely inside the world.
+pub fn place(world: &mut World, pattern: &Pattern, x: u16, y: u16) -> Result<(), WorldError> {
+
+    if x as u32 + pattern.width as u32 > world.width() as u32 || y as u32 + pattern.height as u32 > world.height() as u32 {
+        return Err(WorldError::PatternOutOfBounds);
+    }
+
+    let stamp = World::new_rect(pattern.cells.clone(), pattern.width, pattern.height)?;
+    world.overlay(&stamp, y as u8, x as u8);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::game_of_life::{ World, WorldError };
+
+    use num_traits::Zero;
+
+    fn empty_world(world_size: u8) -> World {
+        World::new(Zero::zero(), world_size).unwrap()
+    }
+
+    #[test]
+    fn glider_translates_diagonally_every_four_generations() {
+
+        let mut world = empty_world(8);
+        super::place(&mut world, &super::glider(), 1, 1).unwrap();
+
+        for _ in 0..4 { world.advance(); }
+
+        let mut expected = empty_world(8);
+        super::place(&mut expected, &super::glider(), 2, 2).unwrap();
+
+        assert_eq!(world.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn place_checks_bounds() {
+
+        let mut world = empty_world(5);
+
+        assert_eq!(super::place(&mut world, &super::glider(), 3, 2), Err(WorldError::PatternOutOfBounds));
+        assert!(super::place(&mut world, &super::blinker(), 0, 5).is_err());
+        assert!(super::place(&mut world, &super::gosper_gun(), 0, 0).is_err());
+        assert_eq!(world.population(), 0);
+
+        super::place(&mut world, &super::blinker(), 2, 4).unwrap();
+        assert_eq!(world.live_cells().collect::<Vec<_>>(), vec![(2, 4), (3, 4), (4, 4)]);
+    }
+
+    #[test]
+    fn by_name_returns_every_named_pattern() {
+
+        for name in super::NAMES {
+            assert!(super::by_name(name).is_some(), "{}", name);
+        }
+
+        let gun = super::by_name("gosper_gun").unwrap();
+
+        assert_eq!((gun.width, gun.height), (36, 9));
+        assert_eq!(gun.cells.count_ones(), 36);
+        assert_eq!(super::by_name("spaceship"), None);
+    }
+}
@@ -0,0 +1,252 @@
+use crate::game_of_life::{ Rule, World };
+
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+const MAX_LINE_LENGTH: usize = 70;
+
+#[derive(Debug, PartialEq)]
+pub enum RleError {
+    MissingHeader,
+    InvalidHeader,
+    PatternTooLarge,
+    InvalidTag(char),
+    CellOutsideOfPattern
+}
+
+impl fmt::Display for RleError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RleError::MissingHeader => write!(f, "missing x = .., y = .. header"),
+            RleError::InvalidHeader => write!(f, "invalid header"),
+            RleError::PatternTooLarge => write!(f, "pattern is larger than 255 x 255"),
+            RleError::InvalidTag(tag) => write!(f, "invalid tag '{}'", tag),
+            RleError::CellOutsideOfPattern => write!(f, "cells lie outside of the size given in the header")
+        }
+    }
+}
+
+impl std::error::Error for RleError {}
+
+/// Parses a pattern in run-length encoded format, skipping `#` comment lines.
+/// Returns the cells, laid out row by row as in a world of the same size, with the pattern width and height.
+pub fn parse_rle(rle: &str) -> Result<(BigUint, u8, u8), RleError> {
+
+    let mut lines = rle.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+    let header = lines.next().ok_or(RleError::MissingHeader)?;
+    let (width, height) = parse_header(header)?;
+
+    let mut seed: BigUint = Zero::zero();
+    let (mut row, mut col) = (0u32, 0u32);
+    let mut count: Option<u32> = None;
+
+    'body: for line in lines {
+        for tag in line.chars() {
+
+            if tag.is_whitespace() { continue; }
+
+            if let Some(digit) = tag.to_digit(10) {
+                count = Some(count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                continue;
+            }
+
+            let run = count.take().unwrap_or(1);
+
+            match tag {
+                'b' | '.' => col = skip(col, run, width)?,
+                'o' => {
+
+                    let end = skip(col, run, width)?;
+
+                    if row >= height as u32 {
+                        return Err(RleError::CellOutsideOfPattern);
+                    }
+
+                    for c in col..end {
+                        seed.set_bit((row * width as u32 + c).into(), true);
+                    }
+
+                    col = end;
+                },
+                '$' => {
+                    row = skip(row, run, height)?;
+                    col = 0;
+                },
+                '!' => break 'body,
+                tag => return Err(RleError::InvalidTag(tag))
+            }
+        }
+    }
+
+    Ok((seed, width, height))
+}
+
+// moves `run` cells past `position`, which may stop at `limit` but not beyond it
+fn skip(position: u32, run: u32, limit: u8) -> Result<u32, RleError> {
+    position.checked_add(run).filter(|&end| end <= limit as u32).ok_or(RleError::CellOutsideOfPattern)
+}
+
+fn parse_header(header: &str) -> Result<(u8, u8), RleError> {
+
+    let mut width = None;
+    let mut height = None;
+
+    for field in header.split(',') {
+
+        let (key, value) = field.split_once('=').ok_or(RleError::InvalidHeader)?;
+        let size = || value.trim().parse::<u32>().map_err(|_| RleError::InvalidHeader);
+
+        match key.trim() {
+            "x" => width = Some(size()?),
+            "y" => height = Some(size()?),
+            _ => ()
+        }
+    }
+
+    let (width, height) = width.zip(height).ok_or(RleError::MissingHeader)?;
+
+    if width > u8::MAX as u32 || height > u8::MAX as u32 {
+        return Err(RleError::PatternTooLarge);
+    }
+
+    Ok((width as u8, height as u8))
+}
+
+pub fn to_rle(world: &World) -> String {
+
+    let width = world.width() as u16;
+
+    encode(width, world.height() as u16, &world.rule(), |row, col| world.get_cell(row * width + col) == 1)
+}
+
+pub(crate) fn encode<F: Fn(u16, u16) -> bool>(width: u16, height: u16, rule: &Rule, alive: F) -> String {
+
+    let mut tokens = Vec::new();
+    let mut last_row = None;
+
+    for row in 0..height {
+
+        let mut runs: Vec<(u16, char)> = Vec::new();
+
+        for col in 0..width {
+
+            let tag = if alive(row, col) {'o'} else {'b'};
+
+            match runs.last_mut() {
+                Some((count, last_tag)) if *last_tag == tag => *count += 1,
+                _ => runs.push((1, tag))
+            }
+        }
+
+        if let Some((_, 'b')) = runs.last() { runs.pop(); }
+        if runs.is_empty() { continue; }
+
+        let skipped = match last_row {
+            Some(last) => row - last,
+            None => row
+        };
+
+        if skipped > 0 { tokens.push(run(skipped, '$')); }
+
+        tokens.extend(runs.into_iter().map(|(count, tag)| run(count, tag)));
+        last_row = Some(row);
+    }
+
+    tokens.push(String::from("!"));
+
+    let mut body = String::new();
+    let mut line_length = 0;
+
+    for token in tokens {
+
+        if line_length + token.len() > MAX_LINE_LENGTH {
+            body.push('\n');
+            line_length = 0;
+        }
+
+        line_length += token.len();
+        body.push_str(&token);
+    }
+
+    format!("x = {}, y = {}, rule = {}\n{}\n", width, height, rule, body)
+}
+
+fn run(count: u16, tag: char) -> String {
+    if count == 1 { tag.to_string() } else { format!("{}{}", count, tag) }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::game_of_life::{ Rule, World };
+
+    use num_bigint::BigUint;
+
+    #[test]
+    fn parse_rle_round_trips_glider() {
+
+        let rle = "#N Glider\n#C The smallest spaceship\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n";
+
+        let (seed, width, height) = super::parse_rle(rle).unwrap();
+
+        assert_eq!((seed.clone(), width, height), (BigUint::parse_bytes(b"111100010",2).unwrap(), 3, 3));
+        assert_eq!(super::to_rle(&World::new_rect(seed, width, height).unwrap()), "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+    }
+
+    #[test]
+    fn parse_rle_handles_runs_and_errors() {
+
+        let (seed, width, height) = super::parse_rle("x = 12, y = 3\n10bo\n$\n2$!").unwrap();
+
+        assert_eq!((width, height), (12, 3));
+        assert_eq!(seed, BigUint::from(1u16 << 10));
+
+        assert_eq!(super::parse_rle("bo$2bo$3o!"), Err(super::RleError::InvalidHeader));
+        assert_eq!(super::parse_rle("# only a comment"), Err(super::RleError::MissingHeader));
+        assert_eq!(super::parse_rle("x = 300, y = 1\no!"), Err(super::RleError::PatternTooLarge));
+        assert_eq!(super::parse_rle("x = 2, y = 1\n3o!"), Err(super::RleError::CellOutsideOfPattern));
+        assert_eq!(super::parse_rle("x = 2, y = 1\nzo!"), Err(super::RleError::InvalidTag('z')));
+    }
+
+    #[test]
+    fn parse_rle_rejects_runs_past_the_header() {
+
+        assert_eq!(super::parse_rle("x = 2, y = 1\n4294967295bbo!"), Err(super::RleError::CellOutsideOfPattern));
+        assert_eq!(super::parse_rle("x = 2, y = 1\n4294967295b2o!"), Err(super::RleError::CellOutsideOfPattern));
+        assert_eq!(super::parse_rle("x = 2, y = 1\n4294967295$4294967295$o!"), Err(super::RleError::CellOutsideOfPattern));
+        assert_eq!(super::parse_rle("x = 2, y = 2\n3b$o!"), Err(super::RleError::CellOutsideOfPattern));
+        assert_eq!(super::parse_rle("x = 2, y = 2\n2b$$!").map(|(seed, _, _)| seed), Ok(BigUint::from(0u8)));
+    }
+
+    #[test]
+    fn to_rle_encodes_runs() {
+
+        /*
+            0100
+            0010
+            1110
+            0000
+        */
+
+        let glider = World::new(BigUint::parse_bytes(b"0000011101000010",2).unwrap(), 4).unwrap();
+
+        assert_eq!(super::to_rle(&glider), "x = 4, y = 4, rule = B3/S23\nbo$2bo$3o!\n");
+    }
+
+    #[test]
+    fn encode_skips_empty_rows_and_wraps_lines() {
+
+        let rle = super::encode(3, 4, &Rule::default(), |row, col| row == 3 && col == 2);
+        assert_eq!(rle, "x = 3, y = 4, rule = B3/S23\n3$2bo!\n");
+
+        let rle = super::encode(3, 1, &Rule::parse("B36/S23").unwrap(), |_, col| col == 0);
+        assert_eq!(rle, "x = 3, y = 1, rule = B36/S23\no!\n");
+
+        let rle = super::encode(100, 2, &Rule::default(), |row, col| row == 1 || col % 2 == 0);
+        assert!(rle.lines().all(|line| line.len() <= super::MAX_LINE_LENGTH));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::editor::{ Editor, Key };
+use crate::game_of_life::World;
+
+use std::io::{ self, Write };
+use std::time::Duration;
+
+use crossterm::{ cursor, execute, queue, terminal };
+use crossterm::event::{ self, Event, KeyCode, KeyEvent, KeyEventKind };
+
+const HELP: &str = "arrows: move, space: toggle, g: stamp a glider, n: next generation, p: play/pause, q: quit";
+
+/// Maps a terminal key press to an editor command.
+pub fn key_from(event: KeyEvent) -> Option<Key> {
+
+    if event.kind != KeyEventKind::Press { return None; }
+
+    match event.code {
+        KeyCode::Up => Some(Key::Up),
+        KeyCode::Down => Some(Key::Down),
+        KeyCode::Left => Some(Key::Left),
+        KeyCode::Right => Some(Key::Right),
+        KeyCode::Char(' ') => Some(Key::Toggle),
+        KeyCode::Char('g') => Some(Key::Stamp),
+        KeyCode::Char('n') => Some(Key::Step),
+        KeyCode::Char('p') => Some(Key::Play),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Key::Quit),
+        _ => None
+    }
+}
+
+/// Edits the world in the terminal until `q` is pressed, advancing every `interval` while playing.
+/// Returns the world as it was left.
+pub fn run(world: World, interval: Duration) -> io::Result<World> {
+
+    let mut stdout = io::stdout();
+    let mut editor = Editor::new(world);
+
+    terminal::enable_raw_mode()?;
+    execute!(stdout, terminal::EnterAlternateScreen)?;
+
+    let result = edit(&mut editor, &mut stdout, interval);
+
+    execute!(stdout, terminal::LeaveAlternateScreen, cursor::Show)?;
+    terminal::disable_raw_mode()?;
+
+    result.map(|_| editor.into_world())
+}
+
+fn edit<W: Write>(editor: &mut Editor, out: &mut W, interval: Duration) -> io::Result<()> {
+
+    loop {
+
+        draw(editor, out)?;
+
+        if editor.is_playing() && !event::poll(interval)? {
+            editor.tick();
+            continue;
+        }
+
+        if let Event::Key(key) = event::read()? {
+            if let Some(key) = key_from(key) {
+                if !editor.handle(key) { return Ok(()); }
+            }
+        }
+    }
+}
+
+fn draw<W: Write>(editor: &Editor, out: &mut W) -> io::Result<()> {
+
+    let world = editor.world();
+
+    queue!(out, cursor::MoveTo(0, 0), terminal::Clear(terminal::ClearType::All))?;
+
+    // raw mode does not return the carriage on new lines
+    write!(out, "{}", world.to_string().replace('\n', "\r\n"))?;
+    write!(out, "Population: {}{}\r\n", world.population(), if editor.is_playing() {" (playing)"} else {""})?;
+    write!(out, "{}", HELP)?;
+
+    // every cell is two columns wide, after the left border
+    let (row, col) = editor.cursor().position();
+    queue!(out, cursor::MoveTo(col as u16 * 2 + 1, row as u16 + 1), cursor::Show)?;
+
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::editor::Key;
+
+    use crossterm::event::{ KeyCode, KeyEvent, KeyEventKind, KeyModifiers };
+
+    #[test]
+    fn key_from_maps_presses_only() {
+
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        assert_eq!(super::key_from(press(KeyCode::Left)), Some(Key::Left));
+        assert_eq!(super::key_from(press(KeyCode::Char(' '))), Some(Key::Toggle));
+        assert_eq!(super::key_from(press(KeyCode::Char('g'))), Some(Key::Stamp));
+        assert_eq!(super::key_from(press(KeyCode::Char('n'))), Some(Key::Step));
+        assert_eq!(super::key_from(press(KeyCode::Char('p'))), Some(Key::Play));
+        assert_eq!(super::key_from(press(KeyCode::Char('q'))), Some(Key::Quit));
+        assert_eq!(super::key_from(press(KeyCode::Char('x'))), None);
+
+        let release = KeyEvent::new_with_kind(KeyCode::Char('q'), KeyModifiers::NONE, KeyEventKind::Release);
+        assert_eq!(super::key_from(release), None);
+    }
+}